@@ -1,6 +1,9 @@
 use std::env;
 
 fn main() {
+    // embedded by the release pipeline, see src/install/checksum.rs
+    println!("cargo:rerun-if-env-changed=WRANGLERJS_SHA256");
+
     if let Ok(profile) = env::var("PROFILE") {
         if profile == "debug" {
             println!("cargo:rustc-cfg=feature={:?}", profile);
@@ -40,9 +40,15 @@ main() {
             ;;
     esac
 
-    which rustup > /dev/null 2>&1
-    need_ok "failed to find Rust installation, is rustup installed?"
-    local _rustup=`which rustup`
+    # with --prefix, the installer doesn't need rustup to find where to go
+    case " $* " in
+        *" --prefix"*)
+            ;;
+        *)
+            which rustup > /dev/null 2>&1
+            need_ok "failed to find Rust installation, is rustup installed? Pass --prefix <dir> to install without it"
+            ;;
+    esac
     local _tardir="wrangler-$VERSION-${_arch}"
     local _url="$UPDATE_ROOT/${_tardir}.tar.gz"
     local _dir="$(mktemp -d 2>/dev/null || ensure mktemp -d -t wrangler)"
@@ -0,0 +1,56 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::commands;
+use crate::settings::project::{Build, Project};
+use crate::terminal::message;
+
+// Build a custom project by running the `command` of its `[build]` section,
+// with the command's output passed through.
+pub fn run_build(project: &Project) -> Result<(), failure::Error> {
+    let build = project.build();
+    let command_line = build.command()?;
+
+    message::working(&format!("Running `{}`...", command_line));
+    commands::run(command(&build)?, &command_line)?;
+
+    check_outputs(&build)?;
+    message::success("Built successfully");
+
+    Ok(())
+}
+
+// The build command, run by the shell from the configured `cwd`.
+pub fn command(build: &Build) -> Result<Command, failure::Error> {
+    let command_line = build.command()?;
+
+    let mut c = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+
+    c.arg(command_line);
+    c.current_dir(build.cwd());
+    Ok(c)
+}
+
+// A build that exits successfully without writing what wrangler.toml says it
+// uploads would otherwise only fail at upload.
+pub fn check_outputs(build: &Build) -> Result<(), failure::Error> {
+    for path in build.upload()?.paths() {
+        if !Path::new(&path).is_file() {
+            failure::bail!(
+                "`{}` did not write {}, which the [build.upload] section of your wrangler.toml uploads",
+                build.command()?,
+                path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
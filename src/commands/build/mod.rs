@@ -1,14 +1,22 @@
 pub mod wranglerjs;
 
+mod custom;
+mod summary;
 mod watch;
+pub use summary::BuildSummary;
 pub use watch::watch_and_build;
+pub use watch::WatchFilter;
 
 use crate::settings::project::{Project, ProjectType};
+use crate::settings::tools::Tools;
 use crate::terminal::message;
 use crate::{commands, install};
 
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::mpsc;
 
 pub fn build(project: &Project) -> Result<(), failure::Error> {
     let project_type = &project.project_type;
@@ -18,8 +26,9 @@ pub fn build(project: &Project) -> Result<(), failure::Error> {
         }
         ProjectType::Rust => {
             let tool_name = "wasm-pack";
-            let binary_path = install::install(tool_name, "rustwasm")?.binary(tool_name)?;
-            let args = ["build", "--target", "no-modules"];
+            let tools = Tools::for_project(project)?;
+            let binary_path = install::install(tool_name, "rustwasm", &tools)?.binary(tool_name)?;
+            let args = project.build().rust().wasm_pack_args();
 
             let command = command(&args, &binary_path);
             let command_name = format!("{:?}", command);
@@ -29,12 +38,69 @@ pub fn build(project: &Project) -> Result<(), failure::Error> {
         ProjectType::Webpack => {
             wranglerjs::run_build(project)?;
         }
+        ProjectType::Custom => {
+            custom::run_build(project)?;
+        }
     }
 
     Ok(())
 }
 
-pub fn command(args: &[&str], binary_path: &PathBuf) -> Command {
+// Print the versions of the tools a build of the project runs, so that the
+// build can be reproduced.
+pub fn print_tool_versions(project: &Project) -> Result<(), failure::Error> {
+    let tools = Tools::for_project(project)?;
+
+    message::info(&format!("wrangler {}", env!("CARGO_PKG_VERSION")));
+    match project.project_type {
+        ProjectType::Rust => {
+            let wasm_pack = install::resolve("wasm-pack", "rustwasm", &tools)?;
+            message::info(&wasm_pack.to_string());
+        }
+        ProjectType::Webpack => {
+            let wasm_pack = install::resolve("wasm-pack", "rustwasm", &tools)?;
+            message::info(&wasm_pack.to_string());
+            if install::target::DEBUG {
+                message::info("wranglerjs from the wrangler sources");
+            } else {
+                message::info(&format!("wranglerjs {}", wranglerjs::version(&tools)));
+            }
+        }
+        ProjectType::JavaScript | ProjectType::Custom => {}
+    }
+
+    Ok(())
+}
+
+// Build the project and report the size of what went into it. Only webpack
+// builds know which modules they're made of.
+pub fn build_and_analyze(project: &Project, report: Option<&Path>) -> Result<(), failure::Error> {
+    match project.project_type {
+        ProjectType::Webpack => wranglerjs::run_build_and_analyze(project, report),
+        _ => {
+            message::warn("Only webpack projects can be analyzed, building without --analyze");
+            build(project)
+        }
+    }
+}
+
+// Build the project, then keep rebuilding it as it changes. A failed build
+// is reported and we carry on watching.
+pub fn build_and_watch(project: &Project) -> Result<(), failure::Error> {
+    if let Err(e) = build(project) {
+        message::user_error(&format!("Build failed: {}", e));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    watch_and_build(project, Some(tx))?;
+
+    // rebuild summaries are printed by the watcher, we just need to stay alive
+    while rx.recv().is_ok() {}
+
+    failure::bail!("Stopped watching your project for changes")
+}
+
+pub fn command<S: AsRef<OsStr>>(args: &[S], binary_path: &PathBuf) -> Command {
     message::working("Compiling your project to WebAssembly...");
 
     let mut c = if cfg!(target_os = "windows") {
@@ -49,3 +115,30 @@ pub fn command(args: &[&str], binary_path: &PathBuf) -> Command {
     c.args(args);
     c
 }
+
+// Write `worker/generated/script.js` for a Rust project by concatenating the
+// wasm-bindgen output in `out_dir` with the `worker/worker.js` glue.
+pub fn generate_rust_script(name: &str, out_dir: &str) -> Result<(), failure::Error> {
+    build_generated_dir()?;
+    concat_js(name, out_dir)
+}
+
+fn build_generated_dir() -> Result<(), failure::Error> {
+    let dir = "./worker/generated";
+    if !Path::new(dir).is_dir() {
+        fs::create_dir("./worker/generated")?;
+    }
+
+    Ok(())
+}
+
+fn concat_js(name: &str, out_dir: &str) -> Result<(), failure::Error> {
+    let bindgen_js_path = Path::new(out_dir).join(format!("{}.js", name));
+    let bindgen_js: String = fs::read_to_string(bindgen_js_path)?.parse()?;
+
+    let worker_js: String = fs::read_to_string("./worker/worker.js")?.parse()?;
+    let js = format!("{} {}", bindgen_js, worker_js);
+
+    fs::write("./worker/generated/script.js", js.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::util::human_size;
+
+use crate::terminal::message;
+
+// What `--watch` reports after each rebuild.
+#[derive(Debug)]
+pub struct BuildSummary {
+    pub duration: Duration,
+    // combined size of the build output, in bytes
+    pub size: u64,
+    pub warnings: usize,
+}
+
+impl BuildSummary {
+    // Measures the size of each output file that exists.
+    pub fn new<P: AsRef<Path>>(duration: Duration, outputs: &[P], warnings: usize) -> BuildSummary {
+        let size = outputs
+            .iter()
+            .filter_map(|output| fs::metadata(output).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        BuildSummary {
+            duration,
+            size,
+            warnings,
+        }
+    }
+
+    pub fn print(&self) {
+        message::success(&self.to_string());
+    }
+}
+
+impl std::fmt::Display for BuildSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let size = human_size(self.size);
+        let warnings = match self.warnings {
+            1 => "1 warning".to_string(),
+            n => format!("{} warnings", n),
+        };
+
+        write!(
+            f,
+            "Rebuilt in {}.{:02}s, output is {}, {}",
+            self.duration.as_secs(),
+            self.duration.subsec_millis() / 10,
+            size,
+            warnings
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_summarizes_a_build() {
+        let summary = BuildSummary {
+            duration: Duration::from_millis(1234),
+            size: 2048,
+            warnings: 1,
+        };
+
+        assert_eq!(
+            summary.to_string(),
+            "Rebuilt in 1.23s, output is 2.0 KiB, 1 warning"
+        );
+    }
+
+    #[test]
+    fn it_skips_missing_outputs() {
+        let summary = BuildSummary::new(Duration::from_secs(0), &["./does/not/exist.js"], 0);
+
+        assert_eq!(summary.size, 0);
+    }
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+
+use crate::settings::project::Watch;
+
+// Ignore files read from the project root, in .gitignore syntax.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".wranglerignore"];
+
+// Dependencies, VCS metadata and build output never trigger a rebuild.
+const DEFAULT_IGNORES: &[&str] = &[
+    "node_modules/",
+    ".git/",
+    "target/",
+    "pkg/",
+    "worker/generated/",
+];
+
+// Decides which changed paths under a project root are worth a rebuild,
+// based on the default ignores, the project's ignore files and the `[watch]`
+// section of its wrangler.toml.
+pub struct WatchFilter {
+    root: PathBuf,
+    canonical_root: Option<PathBuf>,
+    ignores: Vec<IgnorePattern>,
+    include: Option<GlobSet>,
+}
+
+// A single line of an ignore file.
+struct IgnorePattern {
+    globs: Vec<String>,
+    matchers: Vec<GlobMatcher>,
+    negated: bool,
+}
+
+impl WatchFilter {
+    pub fn new(root: &Path, watch: &Watch) -> Result<WatchFilter, failure::Error> {
+        let mut lines: Vec<String> = DEFAULT_IGNORES.iter().map(|l| l.to_string()).collect();
+
+        for ignore_file in IGNORE_FILES {
+            let path = root.join(ignore_file);
+            if path.is_file() {
+                lines.extend(fs::read_to_string(path)?.lines().map(|l| l.to_string()));
+            }
+        }
+
+        lines.extend(watch.exclude());
+
+        WatchFilter::from_patterns(root, &lines, &watch.include())
+    }
+
+    // A filter for arbitrary ignore files, such as a template's `.genignore`.
+    pub fn from_ignore_lines(root: &Path, lines: &[String]) -> Result<WatchFilter, failure::Error> {
+        WatchFilter::from_patterns(root, lines, &[])
+    }
+
+    fn from_patterns(
+        root: &Path,
+        ignore_lines: &[String],
+        include: &[String],
+    ) -> Result<WatchFilter, failure::Error> {
+        let mut ignores = Vec::new();
+        for line in ignore_lines {
+            if let Some(pattern) = IgnorePattern::new(line)? {
+                ignores.push(pattern);
+            }
+        }
+
+        let include = if include.is_empty() {
+            None
+        } else {
+            let mut builder = GlobSetBuilder::new();
+            for glob in include {
+                builder.add(glob_for(glob)?);
+            }
+            Some(builder.build()?)
+        };
+
+        Ok(WatchFilter {
+            root: root.to_path_buf(),
+            canonical_root: root.canonicalize().ok(),
+            ignores,
+            include,
+        })
+    }
+
+    // Paths outside of the project root are never ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let relative = match self.relative(path) {
+            Some(relative) => relative,
+            None => return false,
+        };
+
+        if let Some(include) = &self.include {
+            if !include.is_match(relative) {
+                return true;
+            }
+        }
+
+        self.is_excluded(path)
+    }
+
+    // Whether the ignore patterns alone rule out `path`. Unlike `is_ignored`
+    // this doesn't apply the include globs, which are meant for changed files
+    // rather than the directories holding them: `src/**` doesn't match `src`.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let relative = match self.relative(path) {
+            Some(relative) => relative,
+            None => return false,
+        };
+
+        // like .gitignore, the last matching pattern wins
+        let mut ignored = false;
+        for pattern in &self.ignores {
+            if pattern.is_match(relative) {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+
+    // The ignore patterns as globs relative to the project root, for
+    // watchers that do their own matching (such as webpack's). Negated
+    // patterns cannot be expressed this way and are left out.
+    pub fn globs(&self) -> Vec<String> {
+        self.ignores
+            .iter()
+            .filter(|p| !p.negated)
+            .flat_map(|p| p.globs.clone())
+            .collect()
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        if let Ok(relative) = path.strip_prefix(&self.root) {
+            return Some(relative);
+        }
+
+        match &self.canonical_root {
+            Some(root) => path.strip_prefix(root).ok(),
+            None => None,
+        }
+    }
+}
+
+impl IgnorePattern {
+    fn new(line: &str) -> Result<Option<IgnorePattern>, failure::Error> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let negated = line.starts_with('!');
+        let line = line.trim_start_matches('!');
+
+        // a pattern containing a slash is relative to the root, otherwise it
+        // matches at any depth
+        let line = line.trim_end_matches('/');
+        let pattern = if line.contains('/') {
+            line.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", line)
+        };
+
+        // match the path itself, and everything beneath it if it's a directory
+        let globs = vec![pattern.clone(), format!("{}/**", pattern)];
+        let mut matchers = Vec::new();
+        for glob in &globs {
+            matchers.push(glob_for(glob)?.compile_matcher());
+        }
+
+        Ok(Some(IgnorePattern {
+            globs,
+            matchers,
+            negated,
+        }))
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        self.matchers.iter().any(|m| m.is_match(path))
+    }
+}
+
+fn glob_for(pattern: &str) -> Result<Glob, failure::Error> {
+    match GlobBuilder::new(pattern).literal_separator(true).build() {
+        Ok(glob) => Ok(glob),
+        Err(e) => failure::bail!("Invalid watch pattern `{}`: {}", pattern, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(ignores: &[&str], include: &[&str]) -> WatchFilter {
+        let ignores: Vec<String> = DEFAULT_IGNORES
+            .iter()
+            .chain(ignores)
+            .map(|l| l.to_string())
+            .collect();
+        let include: Vec<String> = include.iter().map(|l| l.to_string()).collect();
+        WatchFilter::from_patterns(Path::new("/project"), &ignores, &include).unwrap()
+    }
+
+    #[test]
+    fn it_ignores_dependencies_and_build_output() {
+        let filter = filter(&[], &[]);
+
+        assert!(filter.is_ignored(Path::new("/project/node_modules/lodash/index.js")));
+        assert!(filter.is_ignored(Path::new("/project/lib/node_modules/a.js")));
+        assert!(filter.is_ignored(Path::new("/project/worker/generated/script.js")));
+        assert!(!filter.is_ignored(Path::new("/project/worker/worker.js")));
+        assert!(!filter.is_ignored(Path::new("/project/src/index.js")));
+    }
+
+    #[test]
+    fn it_follows_gitignore_rules() {
+        let filter = filter(&["# build", "/dist", "*.log", "!keep.log"], &[]);
+
+        assert!(filter.is_ignored(Path::new("/project/dist/index.js")));
+        assert!(!filter.is_ignored(Path::new("/project/src/dist/index.js")));
+        assert!(filter.is_ignored(Path::new("/project/src/debug.log")));
+        assert!(!filter.is_ignored(Path::new("/project/keep.log")));
+    }
+
+    #[test]
+    fn it_only_watches_included_paths() {
+        let filter = filter(&[], &["src/**"]);
+
+        assert!(!filter.is_ignored(Path::new("/project/src/index.js")));
+        assert!(filter.is_ignored(Path::new("/project/README.md")));
+    }
+
+    #[test]
+    fn it_never_ignores_paths_outside_the_root() {
+        let filter = filter(&["*"], &["src/**"]);
+
+        assert!(!filter.is_ignored(Path::new("/elsewhere/lib.rs")));
+    }
+
+    #[test]
+    fn it_lists_globs_without_negations() {
+        let filter = filter(&["!keep.log"], &[]);
+
+        assert!(filter.globs().contains(&"**/node_modules/**".to_string()));
+        assert!(!filter.globs().iter().any(|g| g.contains("keep.log")));
+    }
+}
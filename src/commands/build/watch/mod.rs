@@ -1,20 +1,37 @@
+mod filter;
 mod watcher;
+pub use filter::WatchFilter;
 pub use watcher::wait_for_changes;
 
-use crate::commands::build::{command, wranglerjs};
+use crate::commands::build::{command, custom, generate_rust_script, wranglerjs, BuildSummary};
+use crate::commands::publish::krate::Krate;
 use crate::commands::publish::Package;
-use crate::settings::project::{Project, ProjectType};
+use crate::install;
+use crate::settings::project::{Build, Project, ProjectType};
+use crate::settings::tools::Tools;
 use crate::terminal::message;
-use crate::{commands, install};
 
-use notify::{self, RecursiveMode, Watcher};
+use log::info;
+use notify::{self, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use std::env;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub const COOLDOWN_PERIOD: Duration = Duration::from_millis(2000);
 
+/// the cooldown from the `[watch]` section of wrangler.toml, if any
+pub fn cooldown_period(project: &Project) -> Duration {
+    project
+        .watch()
+        .cooldown
+        .map(Duration::from_millis)
+        .unwrap_or(COOLDOWN_PERIOD)
+}
+
 /// watch a project for changes and re-build it when necessary,
 /// outputting a build event to tx.
 pub fn watch_and_build(
@@ -24,18 +41,27 @@ pub fn watch_and_build(
     let project_type = &project.project_type;
     match project_type {
         ProjectType::JavaScript => {
-            let package = Package::new("./")?;
-            let entry = package.main()?;
-            thread::spawn(move || {
-                let (watcher_tx, watcher_rx) = mpsc::channel();
-                let mut watcher = notify::watcher(watcher_tx, Duration::from_secs(1)).unwrap();
+            // make sure there is an entrypoint before watching anything
+            let entry = Package::new("./")?.main()?;
+
+            let current_dir = env::current_dir()?;
+            let filter = WatchFilter::new(&current_dir, &project.watch())?;
+            // the whole project, so that directories created later are watched
+            // too, with the filter deciding which changes count
+            let paths = [(current_dir.clone(), RecursiveMode::Recursive)];
+            let cooldown = cooldown_period(project);
 
-                watcher.watch(&entry, RecursiveMode::Recursive).unwrap();
-                message::info(&format!("watching {:?}", &entry));
+            let (watcher, watcher_rx) = watch_paths(&paths)?;
+
+            thread::spawn(move || {
+                let _watcher = watcher;
+                message::info(&format!("watching {:?}", &current_dir));
 
                 loop {
-                    match wait_for_changes(&watcher_rx, COOLDOWN_PERIOD) {
+                    match wait_for_changes(&watcher_rx, cooldown, Some(&filter)) {
                         Ok(_path) => {
+                            // nothing to build, the entrypoint is uploaded as is
+                            BuildSummary::new(Duration::from_secs(0), &[&entry], 0).print();
                             if let Some(tx) = tx.clone() {
                                 tx.send(()).expect("--watch change message failed to send");
                             }
@@ -47,34 +73,49 @@ pub fn watch_and_build(
         }
         ProjectType::Rust => {
             let tool_name = "wasm-pack";
-            let binary_path = install::install(tool_name, "rustwasm")?.binary(tool_name)?;
-            let args = ["build", "--target", "no-modules"];
+            let tools = Tools::for_project(project)?;
+            let binary_path = install::install(tool_name, "rustwasm", &tools)?.binary(tool_name)?;
+            let rust_build = project.build().rust();
+            let args = rust_build.wasm_pack_args();
+            let out_dir = rust_build.out_dir();
 
-            let package = Package::new("./")?;
-            let entry = package.main()?;
+            let name = Krate::new("./")?.name.replace("-", "_");
+            let paths = rust_watch_paths()?;
 
-            thread::spawn(move || {
-                let (watcher_tx, watcher_rx) = mpsc::channel();
-                let mut watcher = notify::watcher(watcher_tx, Duration::from_secs(1)).unwrap();
+            // our own output must not trigger a rebuild, wherever it goes
+            let mut watch = project.watch();
+            let mut exclude = watch.exclude();
+            exclude.push(format!(
+                "/{}/",
+                out_dir.trim_start_matches("./").trim_matches('/')
+            ));
+            watch.exclude = Some(exclude);
+
+            let current_dir = env::current_dir()?;
+            let filter = WatchFilter::new(&current_dir, &watch)?;
+            let cooldown = cooldown_period(project);
 
-                let mut path = env::current_dir().expect("current dir");
-                path.push("src");
+            let (watcher, watcher_rx) = watch_paths(&paths)?;
 
-                watcher.watch(&path, RecursiveMode::Recursive).unwrap();
-                watcher.watch(&entry, RecursiveMode::Recursive).unwrap();
-                message::info(&format!("watching {:?} and {:?}", &path, &entry));
+            thread::spawn(move || {
+                let _watcher = watcher;
+                let watched: Vec<&PathBuf> = paths.iter().map(|(path, _)| path).collect();
+                message::info(&format!("watching {:?}", watched));
 
                 loop {
-                    match wait_for_changes(&watcher_rx, COOLDOWN_PERIOD) {
-                        Ok(_path) => {
-                            let command = command(&args, &binary_path);
-                            let command_name = format!("{:?}", command);
-                            if commands::run(command, &command_name).is_ok() {
+                    match wait_for_changes(&watcher_rx, cooldown, Some(&filter)) {
+                        Ok(_path) => match rebuild_rust(&args, &binary_path, &name, &out_dir) {
+                            Ok(summary) => {
+                                summary.print();
                                 if let Some(tx) = tx.clone() {
                                     tx.send(()).expect("--watch change message failed to send");
                                 }
                             }
-                        }
+                            Err(e) => message::user_error(&format!(
+                                "Build failed, waiting for further changes: {}",
+                                e
+                            )),
+                        },
                         Err(_) => message::user_error("Something went wrong while watching."),
                     }
                 }
@@ -83,7 +124,167 @@ pub fn watch_and_build(
         ProjectType::Webpack => {
             wranglerjs::run_build_and_watch(project, tx)?;
         }
+        ProjectType::Custom => {
+            let build = project.build();
+            let upload = build.upload()?;
+
+            let current_dir = env::current_dir()?;
+            let watch_dir = current_dir.join(build.watch_dir());
+            if !watch_dir.is_dir() {
+                failure::bail!(
+                    "Can't watch {} for changes, set `watch_dir` in the [build] section of your wrangler.toml",
+                    watch_dir.display()
+                );
+            }
+
+            // the build's own output must not trigger a rebuild
+            let mut watch = project.watch();
+            let mut exclude = watch.exclude();
+            for path in upload.paths() {
+                exclude.push(format!(
+                    "/{}",
+                    path.display().to_string().trim_start_matches("./")
+                ));
+            }
+            watch.exclude = Some(exclude);
+
+            let filter = WatchFilter::new(&current_dir, &watch)?;
+            let cooldown = cooldown_period(project);
+
+            let (watcher, watcher_rx) =
+                watch_paths(&[(watch_dir.clone(), RecursiveMode::Recursive)])?;
+
+            thread::spawn(move || {
+                let _watcher = watcher;
+                message::info(&format!("watching {:?}", &watch_dir));
+
+                loop {
+                    match wait_for_changes(&watcher_rx, cooldown, Some(&filter)) {
+                        Ok(_path) => match rebuild_custom(&build) {
+                            Ok(summary) => {
+                                summary.print();
+                                if let Some(tx) = tx.clone() {
+                                    tx.send(()).expect("--watch change message failed to send");
+                                }
+                            }
+                            Err(e) => message::user_error(&format!(
+                                "Build failed, waiting for further changes: {}",
+                                e
+                            )),
+                        },
+                        Err(_) => message::user_error("Something went wrong while watching."),
+                    }
+                }
+            });
+        }
     }
 
     Ok(())
 }
+
+// Start watching the paths, before any thread is spawned, so that a path that
+// can't be watched fails the command rather than the thread. The watcher stops
+// once dropped, so the thread receiving its events must keep it.
+fn watch_paths(
+    paths: &[(PathBuf, RecursiveMode)],
+) -> Result<(RecommendedWatcher, mpsc::Receiver<DebouncedEvent>), failure::Error> {
+    let (watcher_tx, watcher_rx) = mpsc::channel();
+    let mut watcher = notify::watcher(watcher_tx, Duration::from_secs(1))?;
+
+    for (path, mode) in paths {
+        watcher.watch(path, *mode).map_err(|e| {
+            failure::format_err!("Can't watch {} for changes: {}", path.display(), e)
+        })?;
+    }
+
+    Ok((watcher, watcher_rx))
+}
+
+fn rebuild_rust(
+    args: &[String],
+    binary_path: &PathBuf,
+    name: &str,
+    out_dir: &str,
+) -> Result<BuildSummary, failure::Error> {
+    let start = Instant::now();
+
+    let warnings = run_counting_warnings(command(args, binary_path))?;
+    generate_rust_script(name, out_dir)?;
+
+    let outputs = [
+        PathBuf::from("./worker/generated/script.js"),
+        Path::new(out_dir).join(format!("{}_bg.wasm", name)),
+    ];
+    Ok(BuildSummary::new(start.elapsed(), &outputs, warnings))
+}
+
+fn rebuild_custom(build: &Build) -> Result<BuildSummary, failure::Error> {
+    let start = Instant::now();
+
+    let warnings = run_counting_warnings(custom::command(build)?)?;
+    custom::check_outputs(build)?;
+
+    let outputs = build.upload()?.paths();
+    Ok(BuildSummary::new(start.elapsed(), &outputs, warnings))
+}
+
+// Run the command, passing its stderr through while counting the compiler
+// warnings in it.
+fn run_counting_warnings(mut command: Command) -> Result<usize, failure::Error> {
+    info!("Running {:?}", command);
+
+    let mut child = command.stderr(Stdio::piped()).spawn()?;
+
+    let mut warnings = 0;
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines() {
+            let line = line?;
+            // skip cargo's "warning: N warnings emitted" style totals
+            if line.starts_with("warning:") && !line.contains("warnings emitted") {
+                warnings += 1;
+            }
+            eprintln!("{}", line);
+        }
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        failure::bail!("failed to execute `{:?}`: exited with {}", command, status)
+    }
+
+    Ok(warnings)
+}
+
+// Everything a Rust worker build depends on: the crate's sources and
+// manifest, its local path dependencies, and the JS glue in `worker/`.
+fn rust_watch_paths() -> Result<Vec<(PathBuf, RecursiveMode)>, failure::Error> {
+    let current_dir = env::current_dir()?;
+    let mut paths = vec![
+        (current_dir.join("src"), RecursiveMode::Recursive),
+        (current_dir.join("Cargo.toml"), RecursiveMode::NonRecursive),
+        // only the top level, worker/generated is our own output
+        (current_dir.join("worker"), RecursiveMode::NonRecursive),
+    ];
+
+    for file in &["Cargo.lock", "build.rs"] {
+        let path = current_dir.join(file);
+        if path.exists() {
+            paths.push((path, RecursiveMode::NonRecursive));
+        }
+    }
+
+    for dependency in Krate::path_dependencies("./")? {
+        let dependency = current_dir.join(dependency);
+        let manifest = dependency.join("Cargo.toml");
+        let src = dependency.join("src");
+
+        if manifest.exists() {
+            paths.push((manifest, RecursiveMode::NonRecursive));
+        }
+        if src.exists() {
+            paths.push((src, RecursiveMode::Recursive));
+        }
+    }
+
+    Ok(paths)
+}
@@ -5,18 +5,28 @@ use std::time::Duration;
 
 use failure::{format_err, Error};
 
+use super::WatchFilter;
 use crate::terminal::message;
 use log::info;
 
-///Add cooldown for all types of events to watching logic
+///Add cooldown for all types of events to watching logic,
+///skipping changes to paths the filter ignores
 pub fn wait_for_changes(
     rx: &Receiver<DebouncedEvent>,
     cooldown: Duration,
+    filter: Option<&WatchFilter>,
 ) -> Result<PathBuf, Error> {
     loop {
         let event = rx.recv()?;
         match get_changed_path_from_event(event) {
             Ok(Some(path)) => {
+                if let Some(filter) = filter {
+                    if filter.is_ignored(&path) {
+                        info!("Ignoring change to {:?}", path);
+                        continue;
+                    }
+                }
+
                 message::working("Detected changes...");
                 //wait for cooldown
                 while let Ok(_) = rx.recv_timeout(cooldown) {
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::util::human_size;
+use base64::decode;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::build::wranglerjs::output::{WasmAsset, WranglerjsOutput};
+use crate::terminal::message;
+
+// How many modules and packages `--analyze` lists.
+const TOP: usize = 10;
+
+// Modules outside of node_modules are grouped under this name.
+const PROJECT_PACKAGE: &str = "(your project)";
+
+// Where the bytes of a webpack build go, as reported by `wrangler build --analyze`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Analysis {
+    // the built scripts and wasm modules, as uploaded
+    pub total: Size,
+    pub modules: Vec<Entry>,
+    pub packages: Vec<Entry>,
+    pub wasm_modules: Vec<Entry>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Size {
+    pub raw: u64,
+    pub gzip: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Entry {
+    pub name: String,
+    pub size: Size,
+}
+
+// What `--report` writes as JSON.
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    analysis: &'a Analysis,
+    previous_total: Option<Size>,
+}
+
+impl Analysis {
+    pub fn new(output: &WranglerjsOutput) -> Analysis {
+        let mut modules: Vec<Entry> = output
+            .modules
+            .iter()
+            .map(|module| Entry {
+                name: module.name.clone(),
+                size: Size {
+                    raw: module.size,
+                    gzip: module.gzip_size,
+                },
+            })
+            .collect();
+        sort_by_size(&mut modules);
+
+        let mut packages: HashMap<String, Size> = HashMap::new();
+        for module in &modules {
+            let package = packages.entry(package_name(&module.name)).or_default();
+            package.raw += module.size.raw;
+            package.gzip += module.size.gzip;
+        }
+        let mut packages: Vec<Entry> = packages
+            .into_iter()
+            .map(|(name, size)| Entry { name, size })
+            .collect();
+        sort_by_size(&mut packages);
+
+        let mut scripts = vec![output.script.as_str()];
+        let mut wasm_assets: Vec<&WasmAsset> = output.wasm_modules.iter().collect();
+        for bundle in &output.bundles {
+            scripts.push(&bundle.script);
+            wasm_assets.extend(bundle.wasm_modules.iter());
+        }
+
+        let mut wasm_modules: Vec<Entry> = wasm_assets
+            .into_iter()
+            .map(|wasm_module| Entry {
+                name: wasm_module.name.clone(),
+                size: measure(&decode(&wasm_module.wasm).unwrap_or_default()),
+            })
+            .collect();
+        sort_by_size(&mut wasm_modules);
+
+        let mut total = Size::default();
+        let sizes = scripts
+            .iter()
+            .map(|script| measure(script.as_bytes()))
+            .chain(wasm_modules.iter().map(|wasm_module| wasm_module.size));
+        for size in sizes {
+            total.raw += size.raw;
+            total.gzip += size.gzip;
+        }
+
+        Analysis {
+            total,
+            modules,
+            packages,
+            wasm_modules,
+        }
+    }
+
+    // The analysis of the previous build, if it was analyzed.
+    pub fn load(path: &Path) -> Result<Option<Analysis>, failure::Error> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        match serde_json::from_str(&fs::read_to_string(path)?) {
+            Ok(analysis) => Ok(Some(analysis)),
+            // written by another version of wrangler, nothing to compare to
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), failure::Error> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn print(&self, previous: Option<&Analysis>) {
+        message::info(&format!(
+            "Built project is {} ({} gzip){}",
+            human_size(self.total.raw),
+            human_size(self.total.gzip),
+            match previous {
+                Some(previous) => format!(
+                    ", {} gzip since the last analyzed build",
+                    human_delta(previous.total.gzip, self.total.gzip)
+                ),
+                None => String::new(),
+            }
+        ));
+
+        message::info(&format!(
+            "Largest packages:\n{}",
+            table(&self.packages, previous.map(|p| &p.packages))
+        ));
+        message::info(&format!(
+            "Largest modules:\n{}",
+            table(&self.modules, previous.map(|p| &p.modules))
+        ));
+
+        if !self.wasm_modules.is_empty() {
+            message::info(&format!(
+                "WebAssembly modules:\n{}",
+                table(&self.wasm_modules, previous.map(|p| &p.wasm_modules))
+            ));
+        }
+    }
+
+    // Write the analysis as JSON or HTML, depending on the extension of `path`.
+    pub fn write_report(
+        &self,
+        path: &Path,
+        previous: Option<&Analysis>,
+    ) -> Result<(), failure::Error> {
+        let report = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::to_string_pretty(&Report {
+                analysis: self,
+                previous_total: previous.map(|p| p.total),
+            })?,
+            Some("html") | Some("htm") => self.to_html(previous),
+            _ => failure::bail!(
+                "Can't tell which report to write to {}, use a path ending in .json or .html",
+                path.display()
+            ),
+        };
+
+        fs::write(path, report)?;
+        message::success(&format!("Wrote the analysis to {}", path.display()));
+
+        Ok(())
+    }
+
+    fn to_html(&self, previous: Option<&Analysis>) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Worker bundle analysis</title>\n</head>\n<body>\n",
+        );
+
+        html += &format!(
+            "<h1>Worker bundle analysis</h1>\n<p>Total: {} ({} gzip)",
+            human_size(self.total.raw),
+            human_size(self.total.gzip)
+        );
+        if let Some(previous) = previous {
+            html += &format!(
+                ", {} gzip since the last analyzed build",
+                human_delta(previous.total.gzip, self.total.gzip)
+            );
+        }
+        html += "</p>\n";
+
+        let sections = [
+            ("Packages", &self.packages),
+            ("Modules", &self.modules),
+            ("WebAssembly modules", &self.wasm_modules),
+        ];
+        for (title, entries) in sections.iter() {
+            if entries.is_empty() {
+                continue;
+            }
+
+            html += &format!(
+                "<h2>{}</h2>\n<table>\n<tr><th>Name</th><th>Size</th><th>Gzip</th></tr>\n",
+                title
+            );
+            for entry in entries.iter() {
+                html += &format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&entry.name),
+                    human_size(entry.size.raw),
+                    human_size(entry.size.gzip)
+                );
+            }
+            html += "</table>\n";
+        }
+
+        html += "</body>\n</html>\n";
+        html
+    }
+}
+
+// The largest entries, a line each.
+fn table(entries: &[Entry], previous: Option<&Vec<Entry>>) -> String {
+    let mut lines = Vec::new();
+    for entry in entries.iter().take(TOP) {
+        let delta = match previous {
+            Some(previous) => match previous.iter().find(|p| p.name == entry.name) {
+                Some(p) => format!("  ({})", human_delta(p.size.gzip, entry.size.gzip)),
+                None => "  (new)".to_string(),
+            },
+            None => String::new(),
+        };
+
+        lines.push(format!(
+            "  {:>10} {:>10} gzip  {}{}",
+            human_size(entry.size.raw),
+            human_size(entry.size.gzip),
+            entry.name,
+            delta
+        ));
+    }
+
+    if entries.len() > TOP {
+        lines.push(format!("  ... and {} more", entries.len() - TOP));
+    }
+    lines.join("\n")
+}
+
+// Largest first, by gzip size as that's what counts against the limit.
+fn sort_by_size(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| {
+        b.size
+            .gzip
+            .cmp(&a.size.gzip)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn measure(bytes: &[u8]) -> Size {
+    let mut e = GzEncoder::new(Vec::new(), Compression::default());
+    e.write_all(bytes).expect("could not write buffer");
+    let gzip = e.finish().expect("failed to compress").len() as u64;
+
+    Size {
+        raw: bytes.len() as u64,
+        gzip,
+    }
+}
+
+// The npm package a module comes from, e.g. `lodash` for
+// `./node_modules/lodash/lodash.js`.
+fn package_name(module: &str) -> String {
+    // drop the loaders, e.g. `./node_modules/css-loader!./src/style.css`
+    let module = module.rsplit('!').next().unwrap_or(module);
+
+    if let Some(i) = module.rfind("node_modules/") {
+        let path = &module[i + "node_modules/".len()..];
+        let mut segments = path.split('/');
+        let name = match segments.next() {
+            Some(scope) if scope.starts_with('@') => match segments.next() {
+                Some(name) => format!("{}/{}", scope, name),
+                None => scope.to_string(),
+            },
+            Some(name) => name.to_string(),
+            None => return PROJECT_PACKAGE.to_string(),
+        };
+        return name;
+    }
+
+    if module.starts_with("(webpack)") {
+        return "webpack".to_string();
+    }
+
+    PROJECT_PACKAGE.to_string()
+}
+
+fn human_delta(before: u64, after: u64) -> String {
+    if after >= before {
+        format!("+{}", human_size(after - before))
+    } else {
+        format!("-{}", human_size(before - after))
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::build::wranglerjs::output::ModuleStats;
+
+    fn module(name: &str, size: u64, gzip_size: u64) -> ModuleStats {
+        ModuleStats {
+            name: name.to_string(),
+            size,
+            gzip_size,
+        }
+    }
+
+    #[test]
+    fn it_finds_the_package_of_a_module() {
+        assert_eq!(package_name("./node_modules/lodash/lodash.js"), "lodash");
+        assert_eq!(
+            package_name("./node_modules/@cloudflare/kv-asset-handler/dist/index.js"),
+            "@cloudflare/kv-asset-handler"
+        );
+        assert_eq!(
+            package_name("./node_modules/a/node_modules/b/index.js"),
+            "b"
+        );
+        assert_eq!(
+            package_name("./node_modules/css-loader!./src/style.css"),
+            PROJECT_PACKAGE
+        );
+        assert_eq!(package_name("(webpack)/buildin/global.js"), "webpack");
+        assert_eq!(package_name("./src/index.js"), PROJECT_PACKAGE);
+    }
+
+    #[test]
+    fn it_groups_modules_by_package() {
+        let output = WranglerjsOutput {
+            modules: vec![
+                module("./src/index.js", 100, 50),
+                module("./node_modules/lodash/lodash.js", 500, 200),
+                module("./node_modules/lodash/fp.js", 300, 100),
+            ],
+            ..Default::default()
+        };
+
+        let analysis = Analysis::new(&output);
+
+        assert_eq!(analysis.modules[0].name, "./node_modules/lodash/lodash.js");
+        assert_eq!(analysis.packages.len(), 2);
+        assert_eq!(analysis.packages[0].name, "lodash");
+        assert_eq!(
+            analysis.packages[0].size,
+            Size {
+                raw: 800,
+                gzip: 300
+            }
+        );
+        assert_eq!(analysis.packages[1].name, PROJECT_PACKAGE);
+    }
+
+    #[test]
+    fn it_formats_deltas() {
+        assert_eq!(human_delta(1024, 3072), "+2.0 KiB");
+        assert_eq!(human_delta(3072, 1024), "-2.0 KiB");
+        assert_eq!(human_delta(10, 10), "+0 B");
+    }
+
+    #[test]
+    fn it_escapes_module_names_in_html() {
+        let analysis = Analysis {
+            modules: vec![Entry {
+                name: "./node_modules/style-loader!<style>".to_string(),
+                size: Size::default(),
+            }],
+            ..Analysis::default()
+        };
+
+        assert!(analysis.to_html(None).contains("&lt;style&gt;"));
+    }
+}
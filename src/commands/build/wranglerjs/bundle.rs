@@ -1,18 +1,30 @@
 use base64::decode;
+use serde_json::Value;
 #[cfg(test)]
 use std::env;
+use std::ffi::OsStr;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 
-use crate::commands::build::wranglerjs::output::WranglerjsOutput;
+#[cfg(test)]
+use crate::commands::build::wranglerjs::output::NamedBundle;
+use crate::commands::build::wranglerjs::output::{WasmAsset, WranglerjsOutput};
 #[cfg(test)]
 use crate::terminal::message;
 
 // Directory where we should write the {Bundle}. It represents the built
 // artifact.
 const BUNDLE_OUT: &str = "./worker";
+
+// Lists the bundles written to their own directories when building every
+// configuration of an exported array of webpack configurations.
+const BUNDLES_MANIFEST: &str = "bundles.json";
+
+// Entries of ./worker a named bundle's directory must not replace.
+const RESERVED_NAMES: &[&str] = &["generated", BUNDLES_MANIFEST];
+
 pub struct Bundle {
     out: String,
 }
@@ -26,8 +38,7 @@ impl Bundle {
         }
     }
 
-    #[cfg(test)]
-    fn new_at(out: String) -> Bundle {
+    pub fn new_at(out: String) -> Bundle {
         Bundle { out }
     }
 
@@ -37,41 +48,193 @@ impl Bundle {
             fs::create_dir(bundle_path)?;
         }
 
+        let manifest_path = bundle_path.join(BUNDLES_MANIFEST);
+
+        if wranglerjs_output.bundles.is_empty() {
+            if manifest_path.exists() {
+                fs::remove_file(&manifest_path)?;
+            }
+
+            return self.write_files(
+                &wranglerjs_output.script,
+                &wranglerjs_output.wasm_modules,
+                &wranglerjs_output.source_map,
+            );
+        }
+
+        // names come from the webpack config, so check them all before
+        // removing anything
+        for named_bundle in &wranglerjs_output.bundles {
+            check_bundle_name(&named_bundle.name)?;
+        }
+
+        // the single bundle of a previous build is replaced by the named ones
+        let single_bundle = [self.script_path(), self.source_map_path()];
+        for path in single_bundle.iter() {
+            if Path::new(path).exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        for (_, path) in self.wasm_modules()? {
+            fs::remove_file(path)?;
+        }
+
+        // each named bundle gets its own directory, e.g. ./worker/{name}
+        let mut names = Vec::new();
+        for named_bundle in &wranglerjs_output.bundles {
+            let bundle = self.named_bundle(&named_bundle.name);
+            if Path::new(&bundle.out).exists() {
+                fs::remove_dir_all(&bundle.out)?;
+            }
+            fs::create_dir(&bundle.out)?;
+
+            bundle.write_files(
+                &named_bundle.script,
+                &named_bundle.wasm_modules,
+                &named_bundle.source_map,
+            )?;
+            names.push(named_bundle.name.clone());
+        }
+
+        fs::write(manifest_path, serde_json::to_string(&names)?)?;
+
+        Ok(())
+    }
+
+    fn write_files(
+        &self,
+        script: &str,
+        wasm_modules: &[WasmAsset],
+        source_map: &Option<String>,
+    ) -> Result<(), failure::Error> {
         let mut script_file = File::create(self.script_path())?;
-        let mut script = create_prologue();
-        script += &wranglerjs_output.script;
+        let mut prologue = create_prologue();
+        prologue += script;
 
-        if let Some(encoded_wasm) = &wranglerjs_output.wasm {
-            let wasm = decode(encoded_wasm).expect("could not decode Wasm in base64");
-            let mut wasm_file = File::create(self.wasm_path())?;
+        // modules the previous build emitted may not be part of this one
+        for (_, path) in self.wasm_modules()? {
+            fs::remove_file(path)?;
+        }
+
+        for wasm_module in wasm_modules {
+            let wasm = decode(&wasm_module.wasm).expect("could not decode Wasm in base64");
+            let mut wasm_file = File::create(self.wasm_path(&wasm_module.name))?;
             wasm_file.write_all(&wasm)?;
         }
 
-        script_file.write_all(script.as_bytes())?;
+        script_file.write_all(prologue.as_bytes())?;
+
+        self.write_source_map(source_map)
+    }
+
+    // The source map from {wranglerjs} maps the script alone, so its sections
+    // are moved past the prologue.
+    fn write_source_map(&self, source_map: &Option<String>) -> Result<(), failure::Error> {
+        let source_map_path = self.source_map_path();
+
+        let source_map = match source_map {
+            Some(source_map) => source_map,
+            None => {
+                if Path::new(&source_map_path).exists() {
+                    fs::remove_file(&source_map_path)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let mut source_map: Value = serde_json::from_str(source_map)?;
+        let (prologue_lines, prologue_columns) = prologue_offset();
+
+        if let Some(sections) = source_map["sections"].as_array_mut() {
+            for section in sections {
+                let offset = &mut section["offset"];
+                let line = offset["line"].as_u64().unwrap_or(0);
+                let column = offset["column"].as_u64().unwrap_or(0);
+
+                // a column offset only applies to the first line of a section
+                if line == 0 {
+                    offset["column"] = (column + prologue_columns).into();
+                }
+                offset["line"] = (line + prologue_lines).into();
+            }
+        }
+
+        fs::write(source_map_path, serde_json::to_string(&source_map)?)?;
 
         Ok(())
     }
 
-    pub fn wasm_path(&self) -> String {
+    // The bundles written by the last build, by name, if it built an array of
+    // webpack configurations.
+    pub fn named_bundles(&self) -> Result<Option<Vec<(String, Bundle)>>, failure::Error> {
+        let manifest_path = Path::new(&self.out).join(BUNDLES_MANIFEST);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        let names: Vec<String> = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+        let bundles = names
+            .into_iter()
+            .map(|name| {
+                let bundle = self.named_bundle(&name);
+                (name, bundle)
+            })
+            .collect();
+
+        Ok(Some(bundles))
+    }
+
+    fn named_bundle(&self, name: &str) -> Bundle {
+        Bundle::new_at(
+            Path::new(&self.out)
+                .join(name)
+                .to_str()
+                .unwrap()
+                .to_string(),
+        )
+    }
+
+    pub fn wasm_path(&self, name: &str) -> String {
         Path::new(&self.out)
-            .join("module.wasm".to_string())
+            .join(format!("{}.wasm", name))
             .to_str()
             .unwrap()
             .to_string()
     }
 
-    pub fn has_wasm(&self) -> bool {
-        Path::new(&self.wasm_path()).exists()
+    // The wasm modules written by the last build, as their name and path.
+    pub fn wasm_modules(&self) -> Result<Vec<(String, String)>, failure::Error> {
+        let bundle_path = Path::new(&self.out);
+        if !bundle_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut wasm_modules = Vec::new();
+        for entry in fs::read_dir(bundle_path)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension() == Some(OsStr::new("wasm")) {
+                let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+                wasm_modules.push((name, path.to_str().unwrap().to_string()));
+            }
+        }
+        wasm_modules.sort();
+
+        Ok(wasm_modules)
     }
 
     pub fn has_webpack_config(&self, webpack_config_path: &PathBuf) -> bool {
         webpack_config_path.exists()
     }
 
-    pub fn get_wasm_binding(&self) -> String {
+    // Every wasm module gets its own binding, starting with this prefix.
+    pub fn wasm_binding_prefix(&self) -> String {
         "wasmprogram".to_string()
     }
 
+    pub fn get_wasm_binding(&self, name: &str) -> String {
+        format!("{}_{}", self.wasm_binding_prefix(), name)
+    }
+
     pub fn script_path(&self) -> String {
         Path::new(&self.out)
             .join("script.js".to_string())
@@ -79,6 +242,39 @@ impl Bundle {
             .unwrap()
             .to_string()
     }
+
+    pub fn source_map_path(&self) -> String {
+        format!("{}.map", self.script_path())
+    }
+
+    // The analysis of the last `wrangler build --analyze`, to compare the next
+    // one with.
+    pub fn analysis_path(&self) -> String {
+        Path::new(&self.out)
+            .join("analysis.json")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+}
+
+// A bundle's name becomes the name of its directory under ./worker, which is
+// removed on every build.
+fn check_bundle_name(name: &str) -> Result<(), failure::Error> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(std::path::is_separator) {
+        failure::bail!(
+            "`{}` can't be the name of a webpack configuration, it must be a plain directory name",
+            name
+        );
+    }
+    if RESERVED_NAMES.contains(&name) {
+        failure::bail!(
+            "`{}` can't be the name of a webpack configuration, wrangler uses ./worker/{}",
+            name,
+            name
+        );
+    }
+    Ok(())
 }
 
 // We inject some code at the top-level of the Worker; called {prologue}.
@@ -90,6 +286,15 @@ pub fn create_prologue() -> String {
     .to_string()
 }
 
+// Where the script starts in the written file, as a line and column.
+fn prologue_offset() -> (u64, u64) {
+    let prologue = create_prologue();
+    let lines = prologue.matches('\n').count();
+    let column = prologue.len() - prologue.rfind('\n').map_or(0, |i| i + 1);
+
+    (lines as u64, column as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,15 +314,14 @@ mod tests {
     fn it_writes_the_bundle_script() {
         let out = create_temp_dir("it_writes_the_bundle_script");
         let wranglerjs_output = WranglerjsOutput {
-            errors: vec![],
             script: "foo".to_string(),
-            wasm: None,
+            ..Default::default()
         };
         let bundle = Bundle::new_at(out.clone());
 
         bundle.write(&wranglerjs_output).unwrap();
         assert!(Path::new(&bundle.script_path()).exists());
-        assert!(!Path::new(&bundle.wasm_path()).exists());
+        assert!(bundle.wasm_modules().unwrap().is_empty());
 
         cleanup(out);
     }
@@ -126,15 +330,138 @@ mod tests {
     fn it_writes_the_bundle_wasm() {
         let out = create_temp_dir("it_writes_the_bundle_wasm");
         let wranglerjs_output = WranglerjsOutput {
-            errors: vec![],
-            script: "".to_string(),
-            wasm: Some("abc".to_string()),
+            wasm_modules: vec![
+                WasmAsset {
+                    name: "foo_bg".to_string(),
+                    wasm: "abc".to_string(),
+                },
+                WasmAsset {
+                    name: "bar_bg".to_string(),
+                    wasm: "ZGVm".to_string(),
+                },
+            ],
+            ..Default::default()
         };
         let bundle = Bundle::new_at(out.clone());
 
         bundle.write(&wranglerjs_output).unwrap();
-        assert!(Path::new(&bundle.wasm_path()).exists());
-        assert!(bundle.has_wasm());
+        assert!(Path::new(&bundle.wasm_path("foo_bg")).exists());
+        assert!(Path::new(&bundle.wasm_path("bar_bg")).exists());
+
+        let names: Vec<String> = bundle
+            .wasm_modules()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["bar_bg", "foo_bg"]);
+        assert_eq!(bundle.get_wasm_binding("foo_bg"), "wasmprogram_foo_bg");
+
+        cleanup(out);
+    }
+
+    #[test]
+    fn it_removes_stale_wasm_modules() {
+        let out = create_temp_dir("it_removes_stale_wasm_modules");
+        let bundle = Bundle::new_at(out.clone());
+        fs::write(bundle.wasm_path("module"), "stale").unwrap();
+
+        let wranglerjs_output = WranglerjsOutput::default();
+
+        bundle.write(&wranglerjs_output).unwrap();
+        assert!(bundle.wasm_modules().unwrap().is_empty());
+
+        cleanup(out);
+    }
+
+    #[test]
+    fn it_writes_the_source_map_past_the_prologue() {
+        let out = create_temp_dir("it_writes_the_source_map_past_the_prologue");
+        let wranglerjs_output = WranglerjsOutput {
+            source_map: Some(
+                r#"{"version":3,"sections":[
+                    {"offset":{"line":0,"column":0},"map":{}},
+                    {"offset":{"line":5,"column":3},"map":{}}
+                ]}"#
+                .to_string(),
+            ),
+            ..Default::default()
+        };
+        let bundle = Bundle::new_at(out.clone());
+
+        bundle.write(&wranglerjs_output).unwrap();
+
+        let source_map: Value =
+            serde_json::from_str(&fs::read_to_string(bundle.source_map_path()).unwrap()).unwrap();
+        let (lines, columns) = prologue_offset();
+        assert_eq!(source_map["sections"][0]["offset"]["line"], lines);
+        assert_eq!(source_map["sections"][0]["offset"]["column"], columns);
+        assert_eq!(source_map["sections"][1]["offset"]["line"], lines + 5);
+        assert_eq!(source_map["sections"][1]["offset"]["column"], 3);
+
+        cleanup(out);
+    }
+
+    #[test]
+    fn it_writes_named_bundles() {
+        let out = create_temp_dir("it_writes_named_bundles");
+        let wranglerjs_output = WranglerjsOutput {
+            bundles: vec![
+                NamedBundle {
+                    name: "api".to_string(),
+                    script: "foo".to_string(),
+                    source_map: None,
+                    wasm_modules: vec![],
+                },
+                NamedBundle {
+                    name: "site".to_string(),
+                    script: "bar".to_string(),
+                    source_map: None,
+                    wasm_modules: vec![WasmAsset {
+                        name: "module".to_string(),
+                        wasm: "abc".to_string(),
+                    }],
+                },
+            ],
+            ..Default::default()
+        };
+        let bundle = Bundle::new_at(out.clone());
+        fs::write(bundle.script_path(), "stale").unwrap();
+        fs::write(bundle.wasm_path("stale"), "stale").unwrap();
+
+        bundle.write(&wranglerjs_output).unwrap();
+        assert!(!Path::new(&bundle.script_path()).exists());
+        assert!(bundle.wasm_modules().unwrap().is_empty());
+
+        let named_bundles = bundle.named_bundles().unwrap().unwrap();
+        assert_eq!(named_bundles.len(), 2);
+        assert_eq!(named_bundles[0].0, "api");
+        assert!(Path::new(&named_bundles[0].1.script_path()).exists());
+        assert!(named_bundles[0].1.wasm_modules().unwrap().is_empty());
+        assert_eq!(named_bundles[1].1.wasm_modules().unwrap().len(), 1);
+
+        cleanup(out);
+    }
+
+    #[test]
+    fn it_refuses_bundle_names_outside_of_their_directory() {
+        let out = create_temp_dir("it_refuses_bundle_names_outside_of_their_directory");
+        let bundle = Bundle::new_at(out.clone());
+        fs::create_dir(Path::new(&out).join("generated")).unwrap();
+
+        for name in &["", ".", "..", "../src", "a/b", "generated", "bundles.json"] {
+            let wranglerjs_output = WranglerjsOutput {
+                bundles: vec![NamedBundle {
+                    name: name.to_string(),
+                    script: "foo".to_string(),
+                    source_map: None,
+                    wasm_modules: vec![],
+                }],
+                ..Default::default()
+            };
+            assert!(bundle.write(&wranglerjs_output).is_err(), "{}", name);
+        }
+        assert!(Path::new(&out).join("generated").exists());
 
         cleanup(out);
     }
@@ -143,8 +470,7 @@ mod tests {
     fn it_has_errors() {
         let wranglerjs_output = WranglerjsOutput {
             errors: vec!["a".to_string(), "b".to_string()],
-            script: "".to_string(),
-            wasm: None,
+            ..Default::default()
         };
         assert!(wranglerjs_output.has_errors());
         assert!(wranglerjs_output.get_errors() == "a\nb");
@@ -1,12 +1,14 @@
+mod analysis;
 mod bundle;
 pub mod output;
 
-use crate::commands::build::watch::wait_for_changes;
-use crate::commands::build::watch::COOLDOWN_PERIOD;
+use crate::commands::build::watch::{cooldown_period, wait_for_changes, WatchFilter};
 
+use crate::commands::build::BuildSummary;
 use crate::commands::publish::package::Package;
 use crate::install;
 use crate::util;
+use analysis::Analysis;
 pub use bundle::Bundle;
 use fs2::FileExt;
 use log::info;
@@ -21,6 +23,7 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::settings::project::Project;
+use crate::settings::tools::Tools;
 use crate::terminal::message;
 
 use notify::{self, RecursiveMode, Watcher};
@@ -35,19 +38,46 @@ use std::time::Duration;
 // {WranglerjsOutput} struct.
 // Note that the ability to pass a fd is platform-specific
 pub fn run_build(project: &Project) -> Result<(), failure::Error> {
+    build_once(project, false)?;
+    Ok(())
+}
+
+// Build, asking {wranglerjs} for the size of every module, and report where
+// the bytes go compared to the previous analyzed build.
+pub fn run_build_and_analyze(
+    project: &Project,
+    report: Option<&Path>,
+) -> Result<(), failure::Error> {
+    let wranglerjs_output = build_once(project, true)?;
+
+    let analysis_path = PathBuf::from(Bundle::new().analysis_path());
+    let previous = Analysis::load(&analysis_path)?;
+
+    let analysis = Analysis::new(&wranglerjs_output);
+    analysis.print(previous.as_ref());
+
+    if let Some(report) = report {
+        analysis.write_report(report, previous.as_ref())?;
+    }
+
+    analysis.save(&analysis_path)
+}
+
+fn build_once(project: &Project, analyze: bool) -> Result<WranglerjsOutput, failure::Error> {
     let (mut command, temp_file, bundle) = setup_build(project)?;
+    if analyze {
+        command.arg("--analyze=1");
+    }
 
     info!("Running {:?}", command);
 
     let status = command.status()?;
 
     if status.success() {
-        let output = fs::read_to_string(temp_file.clone()).expect("could not retrieve ouput");
-
-        let wranglerjs_output: WranglerjsOutput =
-            serde_json::from_str(&output).expect("could not parse wranglerjs output");
+        let wranglerjs_output = read_wranglerjs_output(&temp_file)?;
 
-        write_wranglerjs_output(&bundle, &wranglerjs_output)
+        write_wranglerjs_output(&bundle, &wranglerjs_output)?;
+        Ok(wranglerjs_output)
     } else {
         failure::bail!("failed to execute `{:?}`: exited with {}", command, status)
     }
@@ -60,6 +90,14 @@ pub fn run_build_and_watch(
     let (mut command, temp_file, bundle) = setup_build(project)?;
     command.arg("--watch=1");
 
+    // webpack does its own watching, so hand it our ignore rules
+    let filter = WatchFilter::new(&env::current_dir()?, &project.watch())?;
+    command.arg(format!(
+        "--watch-ignore={}",
+        serde_json::to_string(&filter.globs())?
+    ));
+    let cooldown = cooldown_period(project);
+
     info!("Running {:?} in watch mode", command);
 
     //Turbofish the result of the closure so we can use ?
@@ -76,7 +114,7 @@ pub fn run_build_and_watch(
         let mut is_first = true;
 
         loop {
-            match wait_for_changes(&watcher_rx, COOLDOWN_PERIOD) {
+            match wait_for_changes(&watcher_rx, cooldown, None) {
                 Ok(_) => {
                     if is_first {
                         is_first = false;
@@ -86,14 +124,39 @@ pub fn run_build_and_watch(
                         continue;
                     }
 
-                    let output = fs::read_to_string(&temp_file).expect("could not retrieve ouput");
-                    let wranglerjs_output: WranglerjsOutput =
-                        serde_json::from_str(&output).expect("could not parse wranglerjs output");
-
-                    if write_wranglerjs_output(&bundle, &wranglerjs_output).is_ok() {
-                        if let Some(tx) = tx.clone() {
-                            tx.send(()).expect("--watch change message failed to send");
+                    // a bad rebuild is reported, and we keep watching for the next one
+                    let wranglerjs_output = match read_wranglerjs_output(&temp_file) {
+                        Ok(output) => output,
+                        Err(e) => {
+                            message::user_error(&e.to_string());
+                            continue;
+                        }
+                    };
+
+                    match write_wranglerjs_output(&bundle, &wranglerjs_output) {
+                        Ok(_) => {
+                            let outputs = match bundle_outputs(&bundle) {
+                                Ok(outputs) => outputs,
+                                Err(e) => {
+                                    message::user_error(&e.to_string());
+                                    continue;
+                                }
+                            };
+                            let summary = BuildSummary::new(
+                                Duration::from_millis(wranglerjs_output.time),
+                                &outputs,
+                                wranglerjs_output.warnings.len(),
+                            );
+                            summary.print();
+
+                            if let Some(tx) = tx.clone() {
+                                tx.send(()).expect("--watch change message failed to send");
+                            }
                         }
+                        Err(e) => message::user_error(&format!(
+                            "Build failed, waiting for further changes: {}",
+                            e
+                        )),
                     }
                 }
                 Err(_) => message::user_error("Something went wrong while watching."),
@@ -104,6 +167,18 @@ pub fn run_build_and_watch(
     Ok(())
 }
 
+fn read_wranglerjs_output(temp_file: &PathBuf) -> Result<WranglerjsOutput, failure::Error> {
+    let output = match fs::read_to_string(temp_file) {
+        Ok(output) => output,
+        Err(e) => failure::bail!("could not retrieve wranglerjs output: {}", e),
+    };
+
+    match serde_json::from_str(&output) {
+        Ok(wranglerjs_output) => Ok(wranglerjs_output),
+        Err(e) => failure::bail!("could not parse wranglerjs output: {}", e),
+    }
+}
+
 fn write_wranglerjs_output(
     bundle: &Bundle,
     output: &WranglerjsOutput,
@@ -113,17 +188,43 @@ fn write_wranglerjs_output(
         failure::bail!("Webpack returned an error");
     }
 
+    for warning in &output.warnings {
+        message::warn(warning);
+    }
+
     bundle.write(output)?;
 
-    let msg = format!(
-        "Built successfully, built project size is {}",
-        output.project_size()
-    );
+    if output.bundles.is_empty() {
+        message::success("Built successfully");
+    } else {
+        for named_bundle in &output.bundles {
+            message::success(&format!("Built `{}` successfully", named_bundle.name));
+        }
+    }
 
-    message::success(&msg);
     Ok(())
 }
 
+// The files written by the last build, for every named bundle if there are any.
+fn bundle_outputs(bundle: &Bundle) -> Result<Vec<String>, failure::Error> {
+    let named_bundles = bundle.named_bundles()?.unwrap_or_default();
+    let bundles = if named_bundles.is_empty() {
+        vec![bundle]
+    } else {
+        named_bundles.iter().map(|(_, b)| b).collect()
+    };
+
+    let mut outputs = Vec::new();
+    for bundle in bundles {
+        outputs.push(bundle.script_path());
+        for (_, path) in bundle.wasm_modules()? {
+            outputs.push(path);
+        }
+    }
+
+    Ok(outputs)
+}
+
 //setup a build to run wranglerjs, return the command, the ipc temp file, and the bundle
 fn setup_build(project: &Project) -> Result<(Command, PathBuf, Bundle), failure::Error> {
     for tool in &["node", "npm"] {
@@ -135,11 +236,12 @@ fn setup_build(project: &Project) -> Result<(Command, PathBuf, Bundle), failure:
 
     let node = which::which("node").unwrap();
     let mut command = Command::new(node);
-    let wranglerjs_path = install().expect("could not install wranglerjs");
+    let tools = Tools::for_project(project)?;
+    let wranglerjs_path = install(&tools)?;
     command.arg(wranglerjs_path);
 
     //put path to our wasm_pack as env variable so wasm-pack-plugin can utilize it
-    let wasm_pack_path = install::install("wasm-pack", "rustwasm")?.binary("wasm-pack")?;
+    let wasm_pack_path = install::install("wasm-pack", "rustwasm", &tools)?.binary("wasm-pack")?;
     command.env("WASM_PACK_PATH", wasm_pack_path);
 
     // create a temp file for IPC with the wranglerjs process
@@ -154,7 +256,7 @@ fn setup_build(project: &Project) -> Result<(Command, PathBuf, Bundle), failure:
 
     let bundle = Bundle::new();
 
-    command.arg(format!("--wasm-binding={}", bundle.get_wasm_binding()));
+    command.arg(format!("--wasm-binding={}", bundle.wasm_binding_prefix()));
 
     let webpack_config_path = PathBuf::from(
         &project
@@ -181,6 +283,11 @@ fn setup_build(project: &Project) -> Result<(Command, PathBuf, Bundle), failure:
             "--webpack-config={}",
             &webpack_config_path.to_str().unwrap().to_string()
         ));
+
+        // picks one of an exported array of configurations, or "all" of them
+        if let Some(select) = &project.webpack_config_select {
+            command.arg(format!("--config-select={}", select));
+        }
     }
 
     Ok((command, temp_file, bundle))
@@ -198,6 +305,10 @@ fn run_npm_install(dir: PathBuf) -> Result<(), failure::Error> {
         let mut command = build_npm_command();
         command.current_dir(dir.clone());
         command.arg("install");
+        // from npm's own cache only
+        if install::is_offline() {
+            command.arg("--offline");
+        }
         info!("Running {:?} in directory {:?}", command, dir);
 
         let status = command.status()?;
@@ -252,8 +363,16 @@ fn get_source_dir() -> PathBuf {
     Path::new(&dir).to_path_buf()
 }
 
+// The release of {wranglerjs} to install: the pinned one, else the one
+// matching this version of wrangler.
+pub fn version(tools: &Tools) -> String {
+    tools
+        .version("wranglerjs")
+        .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string())
+}
+
 // Install {wranglerjs} from our GitHub releases
-fn install() -> Result<PathBuf, failure::Error> {
+pub fn install(tools: &Tools) -> Result<PathBuf, failure::Error> {
     let wranglerjs_path = if install::target::DEBUG {
         let source_path = get_source_dir();
         let wranglerjs_path = source_path.join("wranglerjs");
@@ -261,8 +380,8 @@ fn install() -> Result<PathBuf, failure::Error> {
         wranglerjs_path
     } else {
         let tool_name = "wranglerjs";
-        let version = env!("CARGO_PKG_VERSION");
-        let wranglerjs_path = install::install_artifact(tool_name, "cloudflare", version)?;
+        let version = version(tools);
+        let wranglerjs_path = install::install_artifact(tool_name, "cloudflare", &version)?;
         info!("wranglerjs downloaded at: {:?}", wranglerjs_path.path());
         wranglerjs_path.path()
     };
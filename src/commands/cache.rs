@@ -0,0 +1,292 @@
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use crate::util::human_size;
+use chrono::{DateTime, Local};
+
+use crate::install::{self, cache, cache::Entry, cache::Verification};
+use crate::terminal::message;
+
+/// Print where tools are downloaded to.
+pub fn path() -> Result<(), failure::Error> {
+    // the bare path, so scripts can use it as is
+    println!("{}", cache::dir()?.display());
+    Ok(())
+}
+
+/// List every cached tool with its version, size and when a build last used
+/// it.
+pub fn list() -> Result<(), failure::Error> {
+    let root = cache::dir()?;
+    let mut total = 0;
+    let mut rows = Vec::new();
+
+    for (tool_name, _) in install::TOOLS {
+        let mut entries = cache::entries(&root, tool_name)?;
+        entries.sort_by(|a, b| cache::compare_versions(b.version.as_ref(), a.version.as_ref()));
+
+        for entry in entries {
+            let size = cache::size(&entry.path)?;
+            total += size;
+            rows.push((
+                tool_name.to_string(),
+                version_of(&entry),
+                human_size(size),
+                entry
+                    .last_used
+                    .map(|time| {
+                        DateTime::<Local>::from(time)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "never".to_string()),
+            ));
+        }
+    }
+
+    if rows.is_empty() {
+        message::info(&format!("The tool cache at {} is empty", root.display()));
+        return Ok(());
+    }
+
+    let mut table = format!("{:<16} {:<12} {:>10}  LAST USED", "TOOL", "VERSION", "SIZE");
+    for (tool, version, size, last_used) in rows {
+        table.push_str(&format!(
+            "\n{:<16} {:<12} {:>10}  {}",
+            tool, version, size, last_used
+        ));
+    }
+    message::info(&table);
+    message::info(&format!("{} in {}", human_size(total), root.display()));
+
+    Ok(())
+}
+
+/// Remove cached tools: those not used for `older_than`, except the
+/// `keep_latest` newest versions of each tool. Without either, the whole
+/// cache is cleaned.
+pub fn clean(older_than: Option<&str>, keep_latest: Option<usize>) -> Result<(), failure::Error> {
+    let root = cache::dir()?;
+    let cutoff = match older_than {
+        Some(age) => Some(cutoff(age)?),
+        None => None,
+    };
+
+    let mut removed = 0;
+    let mut freed = 0;
+
+    for (tool_name, _) in install::TOOLS {
+        for entry in stale(cache::entries(&root, tool_name)?, cutoff, keep_latest) {
+            // an install of the same version holds this lock until the entry
+            // is complete, and a build may have used it since it was listed
+            let lock = cache::lock(&entry.path)?;
+            if !entry.path.exists() || !is_stale(cache::last_used(&entry.path), cutoff) {
+                continue;
+            }
+
+            let size = cache::size(&entry.path)?;
+            fs::remove_dir_all(&entry.path)?;
+            drop(lock);
+            cache::remove_lock(&entry.path)?;
+            message::info(&format!("Removed {} {}", tool_name, version_of(&entry)));
+
+            removed += 1;
+            freed += size;
+        }
+    }
+
+    if removed == 0 {
+        message::info("Nothing to clean");
+    } else {
+        message::success(&format!(
+            "Removed {} cached tools, freeing {}",
+            removed,
+            human_size(freed)
+        ));
+    }
+
+    Ok(())
+}
+
+// The entries of a tool to clean: those last used before `cutoff`, if any,
+// that aren't among the `keep_latest` newest versions.
+fn stale(
+    mut entries: Vec<Entry>,
+    cutoff: Option<SystemTime>,
+    keep_latest: Option<usize>,
+) -> Vec<Entry> {
+    entries.sort_by(|a, b| cache::compare_versions(b.version.as_ref(), a.version.as_ref()));
+
+    entries
+        .into_iter()
+        .skip(keep_latest.unwrap_or(0))
+        .filter(|entry| is_stale(entry.last_used, cutoff))
+        .collect()
+}
+
+fn is_stale(last_used: Option<SystemTime>, cutoff: Option<SystemTime>) -> bool {
+    match (last_used, cutoff) {
+        (Some(last_used), Some(cutoff)) => last_used < cutoff,
+        _ => true,
+    }
+}
+
+// The time an entry last used `age` ago was used at.
+fn cutoff(age: &str) -> Result<SystemTime, failure::Error> {
+    SystemTime::now()
+        .checked_sub(parse_age(age)?)
+        .ok_or_else(|| {
+            failure::format_err!("`{}` is longer ago than this system's clock goes", age)
+        })
+}
+
+// An age such as `30d`: a number of seconds, minutes, hours, days or weeks.
+fn parse_age(age: &str) -> Result<Duration, failure::Error> {
+    let age = age.trim();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (number, unit) = age.split_at(split);
+
+    let seconds = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => failure::bail!(
+            "Can't tell how old `{}` is, use a number of days like `30d`, or of weeks, hours, minutes or seconds with `w`, `h`, `m` or `s`",
+            age
+        ),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| failure::format_err!("`{}` doesn't start with a number, like `30d`", age))?;
+
+    match number.checked_mul(seconds) {
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => failure::bail!("`{}` is too long ago", age),
+    }
+}
+
+fn version_of(entry: &Entry) -> String {
+    entry
+        .version
+        .clone()
+        .unwrap_or_else(|| "(unknown version)".to_string())
+}
+
+/// Check every tool in the cache against the checksums recorded when it was
+/// downloaded, removing those that don't match so they're downloaded again.
+/// Those downloaded without checksums are only reported.
+pub fn verify() -> Result<(), failure::Error> {
+    let root = cache::dir()?;
+    let mut checked = 0;
+    let mut removed = 0;
+    let mut unverified = 0;
+
+    for (tool_name, _) in install::TOOLS {
+        for entry in cache::entries(&root, tool_name)? {
+            let _lock = cache::lock(&entry.path)?;
+            let name = format!("{} {}", tool_name, version_of(&entry));
+            checked += 1;
+
+            match cache::verify(&entry.path)? {
+                Verification::Verified => message::success(&format!("{} is intact", name)),
+                Verification::Mismatch(files) => {
+                    message::warn(&format!(
+                        "{} doesn't match its checksums ({}), removing it",
+                        name,
+                        files.join(", ")
+                    ));
+                    fs::remove_dir_all(&entry.path)?;
+                    removed += 1;
+                }
+                Verification::Unverifiable => {
+                    message::warn(&format!(
+                        "{} was downloaded without checksums, so it can't be verified",
+                        name
+                    ));
+                    unverified += 1;
+                }
+            }
+        }
+    }
+
+    if unverified > 0 {
+        message::warn(&format!(
+            "{} of {} cached tools are unverified, `wrangler cache clean` removes them",
+            unverified, checked
+        ));
+    }
+
+    if checked == 0 {
+        message::info(&format!("The tool cache at {} is empty", root.display()));
+    } else if removed > 0 {
+        failure::bail!(
+            "{} of {} cached tools failed verification and were removed, they will be downloaded again when needed",
+            removed,
+            checked
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(version: &str, days_ago: u64) -> Entry {
+        Entry {
+            version: Some(version.to_string()),
+            path: PathBuf::from(format!("wasm-pack-{}", version)),
+            last_used: Some(SystemTime::now() - Duration::from_secs(days_ago * 24 * 60 * 60)),
+        }
+    }
+
+    fn versions(entries: Vec<Entry>) -> Vec<String> {
+        entries.into_iter().map(|e| e.version.unwrap()).collect()
+    }
+
+    #[test]
+    fn it_parses_ages() {
+        assert_eq!(parse_age("30d").unwrap(), Duration::from_secs(30 * 86400));
+        assert_eq!(parse_age("2w").unwrap(), Duration::from_secs(14 * 86400));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 3600));
+        assert_eq!(parse_age("7").unwrap(), Duration::from_secs(7 * 86400));
+        assert!(parse_age("3 months").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("99999999999999w").is_err());
+        assert!(cutoff("10000000000000000000s").is_err());
+    }
+
+    #[test]
+    fn it_keeps_the_latest_and_recently_used_versions() {
+        let entries = || {
+            vec![
+                entry("0.8.1", 90),
+                entry("0.10.0", 60),
+                entry("0.9.1", 1),
+                entry("0.7.0", 120),
+            ]
+        };
+        let month_ago = Some(SystemTime::now() - parse_age("30d").unwrap());
+
+        assert_eq!(
+            versions(stale(entries(), None, None)),
+            vec!["0.10.0", "0.9.1", "0.8.1", "0.7.0"]
+        );
+        assert_eq!(
+            versions(stale(entries(), None, Some(2))),
+            vec!["0.8.1", "0.7.0"]
+        );
+        assert_eq!(
+            versions(stale(entries(), month_ago, None)),
+            vec!["0.10.0", "0.8.1", "0.7.0"]
+        );
+        assert_eq!(
+            versions(stale(entries(), month_ago, Some(1))),
+            vec!["0.8.1", "0.7.0"]
+        );
+    }
+}
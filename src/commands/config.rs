@@ -6,6 +6,7 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 
 use crate::settings::global_user::{get_global_config_dir, GlobalUser};
+use crate::settings::tools::Tools;
 
 // set the permissions on the dir, we want to avoid that other user reads to
 // file
@@ -20,7 +21,13 @@ pub fn set_file_mode(file: &PathBuf) {
 pub fn global_config(email: String, api_key: String) -> Result<(), failure::Error> {
     let s = GlobalUser { email, api_key };
 
-    let toml = toml::to_string(&s)?;
+    let mut toml = toml::to_string(&s)?;
+
+    // keep the tool versions pinned in the current config, if it can be read
+    let tools = Tools::global().unwrap_or_default();
+    if !tools.is_empty() {
+        toml.push_str(&format!("\n[tools]\n{}", toml::to_string(&tools)?));
+    }
 
     let config_dir = get_global_config_dir().expect("could not find global config directory");
     fs::create_dir_all(&config_dir)?;
@@ -0,0 +1,131 @@
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::http;
+use crate::settings::project::ProjectType;
+use crate::terminal::message;
+
+// The index built into wrangler.
+const BUNDLED_INDEX: &str = include_str!("templates.toml");
+
+// The templates `wrangler generate` knows by name.
+#[derive(Debug, Deserialize)]
+pub struct Index {
+    templates: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub project_type: ProjectType,
+    pub repo: String,
+    pub branch: Option<String>,
+    pub subdir: Option<String>,
+}
+
+impl Index {
+    // The index at `location`, a URL or a path, else the bundled one.
+    pub fn load(location: Option<&str>) -> Result<Index, failure::Error> {
+        let index = match location {
+            None => BUNDLED_INDEX.to_string(),
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                let mut res = http::client().get(url).send()?;
+                if !res.status().is_success() {
+                    failure::bail!(
+                        "failed to fetch the template index {}: {}",
+                        url,
+                        res.status()
+                    );
+                }
+                res.text()?
+            }
+            Some(path) => fs::read_to_string(path).map_err(|e| {
+                failure::format_err!("failed to read the template index {}: {}", path, e)
+            })?,
+        };
+
+        Index::parse(&index)
+            .map_err(|e| failure::format_err!("the template index is invalid: {}", e))
+    }
+
+    fn parse(index: &str) -> Result<Index, failure::Error> {
+        Ok(toml::from_str(index)?)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Entry> {
+        self.templates.iter().find(|entry| entry.name == name)
+    }
+
+    // Print the catalog, for `wrangler generate --list`.
+    pub fn print(&self) {
+        let width = self
+            .templates
+            .iter()
+            .map(|entry| entry.name.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut catalog = String::new();
+        for entry in &self.templates {
+            catalog.push_str(&format!(
+                "\n  {:<width$}  {:<8} {}",
+                entry.name,
+                entry.project_type.to_string(),
+                entry.description,
+                width = width
+            ));
+            catalog.push_str(&format!(
+                "\n  {:<width$}  {:<8} {}",
+                "",
+                "",
+                entry.repo,
+                width = width
+            ));
+        }
+        message::info(&format!(
+            "Templates you can generate by name, with `wrangler generate <name> <template>`:{}",
+            catalog
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_bundled_index() {
+        let index = Index::parse(BUNDLED_INDEX).unwrap();
+
+        let worker = index.find("worker").unwrap();
+        assert_eq!(worker.repo, "https://github.com/cloudflare/worker-template");
+        assert_eq!(worker.project_type.to_string(), "webpack");
+        assert_eq!(index.find("rust").unwrap().project_type.to_string(), "rust");
+        assert!(index
+            .find("https://github.com/cloudflare/worker-template")
+            .is_none());
+    }
+
+    #[test]
+    fn it_reads_the_branch_and_subdir_of_a_template() {
+        let index = Index::parse(
+            r#"
+            [[templates]]
+            name = "hello"
+            description = "Hello, world"
+            type = "javascript"
+            repo = "https://github.com/someone/templates"
+            branch = "stable"
+            subdir = "hello"
+            "#,
+        )
+        .unwrap();
+
+        let hello = index.find("hello").unwrap();
+        assert_eq!(hello.branch, Some("stable".to_string()));
+        assert_eq!(hello.subdir, Some("hello".to_string()));
+    }
+}
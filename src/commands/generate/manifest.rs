@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+use which::which;
+
+use crate::commands;
+use crate::terminal::{interactive, message};
+
+// What a template declares about itself, next to its files. It is left out
+// of generated projects.
+pub const MANIFEST_FILE: &str = "wrangler-template.toml";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub placeholders: Vec<Placeholder>,
+    // Run in the new project once generated. Templates without a manifest
+    // get a git repository.
+    setup: Option<Vec<Action>>,
+}
+
+// A value the template's files refer to as `{{ name }}`, beyond the
+// project name and authors.
+#[derive(Debug, Deserialize)]
+pub struct Placeholder {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum Action {
+    // `git init`, and commit the generated files
+    GitInit,
+    // `npm install`, for templates with a package.json
+    Install,
+    // any command, run by the shell
+    Run { command: String },
+}
+
+impl Manifest {
+    // The manifest of the template at `root`, if it has one.
+    pub fn load(root: &Path) -> Result<Manifest, failure::Error> {
+        let path = root.join(MANIFEST_FILE);
+        if !path.is_file() {
+            return Ok(Manifest::default());
+        }
+
+        Manifest::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(manifest: &str) -> Result<Manifest, failure::Error> {
+        toml::from_str(manifest)
+            .map_err(|e| failure::format_err!("The template's {} is invalid: {}", MANIFEST_FILE, e))
+    }
+
+    // Add a value for every placeholder of the template to `values`: the one
+    // passed with `--define`, else the one typed in at a prompt, else the
+    // default.
+    pub fn fill(&self, values: &mut HashMap<String, String>) -> Result<(), failure::Error> {
+        for placeholder in &self.placeholders {
+            if values.contains_key(&placeholder.name) {
+                continue;
+            }
+
+            let value = if interactive::is_interactive() {
+                prompt(placeholder)?
+            } else {
+                match &placeholder.default {
+                    Some(default) => default.to_string(),
+                    None => failure::bail!(
+                        "The template needs a value for `{}`, pass it with `--define {}=<value>`",
+                        placeholder.name,
+                        placeholder.name
+                    ),
+                }
+            };
+            values.insert(placeholder.name.clone(), value);
+        }
+
+        Ok(())
+    }
+
+    pub fn setup(&self) -> Vec<Action> {
+        self.setup.clone().unwrap_or_else(|| vec![Action::GitInit])
+    }
+}
+
+// The setup actions allowed to run. Commands of a template run as the user,
+// so they are listed and only run once confirmed, or with `yes` when there
+// is no terminal to confirm on; without that they are skipped.
+pub fn confirm(actions: Vec<Action>, yes: bool) -> Result<Vec<Action>, failure::Error> {
+    let commands: Vec<&str> = actions.iter().filter_map(Action::command).collect();
+    if commands.is_empty() || yes {
+        return Ok(actions);
+    }
+
+    let mut msg = "The template sets up your project by running:".to_string();
+    for command in &commands {
+        msg.push_str(&format!("\n    {}", command));
+    }
+    message::info(&msg);
+
+    let confirmed = if interactive::is_interactive() {
+        interactive::confirm("Run these commands?", false)?
+    } else {
+        message::warn("Pass `--yes` to run the template's commands without a terminal");
+        false
+    };
+    if confirmed {
+        return Ok(actions);
+    }
+
+    message::info("Skipped the template's commands");
+    Ok(actions
+        .into_iter()
+        .filter(|action| action.command().is_none())
+        .collect())
+}
+
+impl Action {
+    // The command line of a `run` action.
+    fn command(&self) -> Option<&str> {
+        match self {
+            Action::Run { command } => Some(command),
+            _ => None,
+        }
+    }
+
+    // Run the action in `dir`. A failed action leaves a project that works
+    // once its setup is finished by hand, so it is only a warning.
+    pub fn run(&self, dir: &Path) {
+        let result = match self {
+            Action::GitInit => git_init(dir),
+            Action::Install => {
+                if dir.join("package.json").is_file() {
+                    shell("npm install", dir)
+                } else {
+                    Ok(())
+                }
+            }
+            Action::Run { command } => shell(command, dir),
+        };
+
+        if let Err(e) = result {
+            message::warn(&format!(
+                "{}, you may have to finish setting up your project yourself",
+                e
+            ));
+        }
+    }
+}
+
+fn git_init(dir: &Path) -> Result<(), failure::Error> {
+    let git = match which("git") {
+        Ok(git) => git,
+        Err(_) => failure::bail!("git is not installed, so your project has no repository"),
+    };
+
+    for args in &[
+        vec!["init", "--quiet"],
+        vec!["add", "--all"],
+        vec!["commit", "--quiet", "--message", "Initial commit"],
+    ] {
+        let mut command = Command::new(&git);
+        command.args(args).current_dir(dir);
+        commands::run(command, &format!("git {}", args[0]))?;
+    }
+
+    Ok(())
+}
+
+fn shell(command_line: &str, dir: &Path) -> Result<(), failure::Error> {
+    message::working(&format!("Running `{}`...", command_line));
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    command.arg(command_line).current_dir(dir);
+
+    commands::run(command, command_line)
+}
+
+fn prompt(placeholder: &Placeholder) -> Result<String, failure::Error> {
+    let question = placeholder
+        .description
+        .clone()
+        .unwrap_or_else(|| placeholder.name.clone());
+    let value = match &placeholder.default {
+        Some(default) => interactive::ask(&format!("{} [{}]:", question, default))?,
+        None => interactive::ask(&format!("{}:", question))?,
+    };
+
+    match &placeholder.default {
+        Some(default) if value.is_empty() => Ok(default.to_string()),
+        _ => Ok(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_placeholders_and_setup_actions() {
+        let manifest = Manifest::parse(
+            r#"
+            [[placeholders]]
+            name = "greeting"
+            description = "What the worker answers with"
+            default = "Hello worker!"
+
+            [[setup]]
+            action = "git-init"
+
+            [[setup]]
+            action = "run"
+            command = "npm run build"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.placeholders[0].name, "greeting");
+        assert_eq!(
+            manifest.setup(),
+            vec![
+                Action::GitInit,
+                Action::Run {
+                    command: "npm run build".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn it_runs_template_commands_only_when_confirmed() {
+        let actions = vec![
+            Action::GitInit,
+            Action::Run {
+                command: "npm run build".to_string(),
+            },
+            Action::Install,
+        ];
+
+        assert_eq!(confirm(actions.clone(), true).unwrap(), actions);
+        // tests have no terminal to confirm on
+        assert_eq!(
+            confirm(actions, false).unwrap(),
+            vec![Action::GitInit, Action::Install]
+        );
+    }
+
+    #[test]
+    fn it_sets_up_a_repository_by_default() {
+        assert_eq!(Manifest::default().setup(), vec![Action::GitInit]);
+    }
+}
@@ -0,0 +1,200 @@
+mod index;
+mod manifest;
+mod template;
+
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use log::info;
+use which::which;
+
+use crate::settings::project::{Project, ProjectType};
+use crate::terminal::{emoji, message};
+use index::Index;
+use manifest::Manifest;
+use template::{Source, Template};
+
+// How to fetch and fill in a template, beyond where it is.
+pub struct GenerateOptions<'a> {
+    pub branch: Option<&'a str>,
+    pub subdir: Option<&'a str>,
+    // a URL or path to use instead of the bundled template index
+    pub index: Option<&'a str>,
+    // placeholder values, as `key=value`
+    pub defines: Vec<&'a str>,
+    // skip the setup actions of the template
+    pub no_setup: bool,
+    // run the template's setup commands without asking
+    pub yes: bool,
+}
+
+// Generate a project from `template`: a name from the template index, a git
+// repository, a local directory or a tarball. Without one, the index's
+// `worker` template is used, or `rust` for Rust projects.
+pub fn generate(
+    name: &str,
+    template: Option<&str>,
+    pt: Option<ProjectType>,
+    options: &GenerateOptions,
+) -> Result<(), failure::Error> {
+    let mut values = values(name);
+    for define in &options.defines {
+        let (key, value) = parse_define(define)?;
+        values.insert(key, value);
+    }
+
+    let index = Index::load(options.index)?;
+    let template = template.unwrap_or(match pt {
+        Some(ProjectType::Rust) => "rust",
+        _ => "worker",
+    });
+
+    let mut branch = options.branch.map(String::from);
+    let mut subdir = options.subdir.map(String::from);
+    let mut indexed_type = None;
+    let source = match index.find(template) {
+        Some(entry) if !Path::new(template).exists() => {
+            branch = branch.or_else(|| entry.branch.clone());
+            subdir = subdir.or_else(|| entry.subdir.clone());
+            indexed_type = Some(entry.project_type.clone());
+            Source::parse(&entry.repo)
+        }
+        _ if is_short_name(template) => failure::bail!(
+            "There is no template named `{}`, run `wrangler generate --list` to see them all",
+            template
+        ),
+        _ => Source::parse(template),
+    };
+
+    info!("Fetching template {:?}", source);
+    let template = Template::fetch(&source, as_str(&branch), as_str(&subdir))?;
+
+    // the type given on the command line wins over the one the template
+    // declares, in the index or in its own wrangler.toml
+    let pt = match pt.or(indexed_type) {
+        Some(pt) => pt,
+        None => template.project_type()?.unwrap_or_default(),
+    };
+    let msg = format!(
+        "{} Generating a new {} worker project with name '{}'...",
+        emoji::SHEEP,
+        pt,
+        name
+    );
+    message::working(&msg);
+
+    let manifest = Manifest::load(template.root())?;
+    manifest.fill(&mut values)?;
+
+    let dir = Path::new(name);
+    template.instantiate(dir, &values)?;
+
+    let mut project = Project::scaffold(name.to_string(), pt);
+    if let Some(account_id) = values.get("account_id") {
+        project.account_id = account_id.to_string();
+    }
+    if let Some(zone_id) = values.get("zone_id") {
+        project.zone_id = Some(zone_id.to_string());
+    }
+    if let Some(route) = values.get("route") {
+        project.route = Some(route.to_string());
+    }
+    project.write(dir)?;
+
+    if !options.no_setup {
+        for action in manifest::confirm(manifest.setup(), options.yes)? {
+            action.run(dir);
+        }
+    }
+
+    Ok(())
+}
+
+// Print the templates of the index, for `wrangler generate --list`.
+pub fn list_templates(index: Option<&str>) -> Result<(), failure::Error> {
+    let index = Index::load(index)?;
+    index.print();
+    Ok(())
+}
+
+// A template name rather than a URL or a path.
+fn is_short_name(template: &str) -> bool {
+    !template.contains('/')
+        && !template.contains('\\')
+        && !template.contains('.')
+        && !Path::new(template).exists()
+}
+
+fn as_str(value: &Option<String>) -> Option<&str> {
+    match value {
+        Some(value) => Some(value),
+        None => None,
+    }
+}
+
+// A `--define key=value`.
+fn parse_define(define: &str) -> Result<(String, String), failure::Error> {
+    let mut parts = define.splitn(2, '=');
+    match (parts.next(), parts.next()) {
+        (Some(key), Some(value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => failure::bail!("`--define {}` should be `--define <key>=<value>`", define),
+    }
+}
+
+// What the placeholders of a template are replaced with, and the ids and
+// route of the new wrangler.toml given `account_id`, `zone_id` and `route`.
+fn values(name: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    values.insert("project-name".to_string(), name.to_string());
+    values.insert("crate_name".to_string(), name.replace('-', "_"));
+    values.insert("authors".to_string(), authors());
+    values
+}
+
+// Like cargo, the author from git's configuration, else the current user.
+fn authors() -> String {
+    let git_config = |key: &str| -> Option<String> {
+        let output = Command::new(which("git").ok()?)
+            .args(["config", "--get", key])
+            .output()
+            .ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !value.is_empty() {
+            Some(value)
+        } else {
+            None
+        }
+    };
+
+    let name = git_config("user.name")
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .unwrap_or_default();
+    match git_config("user.email") {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_defines() {
+        assert_eq!(
+            parse_define("account_id=abc=123").unwrap(),
+            ("account_id".to_string(), "abc=123".to_string())
+        );
+        assert_eq!(
+            parse_define("greeting=").unwrap(),
+            ("greeting".to_string(), String::new())
+        );
+        assert!(parse_define("greeting").is_err());
+        assert!(parse_define("=value").is_err());
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use flate2::read::GzDecoder;
+use regex::{Captures, Regex};
+use uuid::Uuid;
+use which::which;
+
+use super::manifest::MANIFEST_FILE;
+use crate::commands;
+use crate::commands::build::WatchFilter;
+use crate::install;
+use crate::settings::project::ProjectType;
+
+// Paths to leave out of generated projects, relative to the template root,
+// in .gitignore syntax. Kept compatible with cargo-generate templates.
+const IGNORE_FILE: &str = ".genignore";
+
+// `{{ project-name }}`, with or without the spaces.
+const PLACEHOLDER_PATTERN: &str = r"\{\{\s*(?P<key>[A-Za-z_][\w-]*)\s*\}\}";
+
+// Where a template comes from, as given to `wrangler generate`.
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    // downloaded as a tarball, so that no git is needed
+    GitHub { owner: String, repo: String },
+    Git(String),
+    Dir(PathBuf),
+    // a path or a URL
+    Tarball(String),
+}
+
+impl Source {
+    pub fn parse(template: &str) -> Source {
+        if template.ends_with(".tar.gz") || template.ends_with(".tgz") {
+            return Source::Tarball(template.to_string());
+        }
+
+        if Path::new(template).is_dir() {
+            return Source::Dir(PathBuf::from(template));
+        }
+
+        let github = Regex::new(
+            r"^(?:https?://)?(?:www\.)?github\.com/(?P<owner>[^/]+)/(?P<repo>[^/]+?)(?:\.git)?/?$",
+        )
+        .unwrap();
+        match github.captures(template) {
+            Some(caps) => Source::GitHub {
+                owner: caps["owner"].to_string(),
+                repo: caps["repo"].to_string(),
+            },
+            None => Source::Git(template.to_string()),
+        }
+    }
+}
+
+// A template ready to be instantiated, in a temporary directory unless it is
+// a local one.
+pub struct Template {
+    root: PathBuf,
+    temp: Option<PathBuf>,
+}
+
+impl Template {
+    // Fetch a template, from `branch` for git templates, using only `subdir`
+    // of it for repositories holding several templates.
+    pub fn fetch(
+        source: &Source,
+        branch: Option<&str>,
+        subdir: Option<&str>,
+    ) -> Result<Template, failure::Error> {
+        if let Some(subdir) = subdir {
+            check_subdir(subdir)?;
+        }
+
+        if branch.is_some() {
+            match source {
+                Source::GitHub { .. } | Source::Git(_) => (),
+                _ => failure::bail!("--branch only applies to templates in a git repository"),
+            }
+        }
+
+        let mut template = match source {
+            Source::Dir(path) => Template {
+                root: path.to_path_buf(),
+                temp: None,
+            },
+            _ => {
+                let temp = env::temp_dir()
+                    .join(format!("wrangler-template-{}", Uuid::new_v4().to_simple()));
+                fs::create_dir_all(&temp)?;
+                // removes the temporary directory should fetching fail
+                let mut template = Template {
+                    root: temp.clone(),
+                    temp: Some(temp.clone()),
+                };
+                template.root = fetch_into(source, branch, &temp)?;
+                template
+            }
+        };
+
+        if let Some(subdir) = subdir {
+            let root = template.root.join(subdir);
+            if !root.is_dir() {
+                failure::bail!("The template has no `{}` directory", subdir);
+            }
+            template.root = root;
+        }
+
+        Ok(template)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // The project type the template declares in its own wrangler.toml.
+    pub fn project_type(&self) -> Result<Option<ProjectType>, failure::Error> {
+        let path = self.root.join("wrangler.toml");
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let toml: toml::Value = toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| failure::format_err!("The template's wrangler.toml is invalid: {}", e))?;
+        match toml.get("type").and_then(|t| t.as_str()) {
+            Some(project_type) => Ok(Some(ProjectType::from_str(project_type)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Copy the template to `dst`, substituting the placeholders in file names
+    // and contents with `values`, and leaving out the ignored files.
+    pub fn instantiate(
+        &self,
+        dst: &Path,
+        values: &HashMap<String, String>,
+    ) -> Result<(), failure::Error> {
+        if dst.exists() {
+            failure::bail!(
+                "`{}` already exists, pick another name for your project",
+                dst.display()
+            );
+        }
+
+        let ignore_path = self.root.join(IGNORE_FILE);
+        let ignore_lines: Vec<String> = if ignore_path.is_file() {
+            fs::read_to_string(ignore_path)?
+                .lines()
+                .map(String::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let filter = WatchFilter::from_ignore_lines(&self.root, &ignore_lines)?;
+        let placeholder = Regex::new(PLACEHOLDER_PATTERN)?;
+
+        let root = self.root.canonicalize()?;
+        fs::create_dir_all(dst)?;
+        copy_dir(&root, &root, dst, &filter, &placeholder, values)
+    }
+}
+
+impl Drop for Template {
+    fn drop(&mut self) {
+        if let Some(temp) = &self.temp {
+            drop(fs::remove_dir_all(temp));
+        }
+    }
+}
+
+// A subdirectory of a template must stay inside of it.
+fn check_subdir(subdir: &str) -> Result<(), failure::Error> {
+    let outside = Path::new(subdir)
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)));
+    if outside {
+        failure::bail!(
+            "`{}` is not a directory of the template, use a relative path like `templates/router`",
+            subdir
+        );
+    }
+    Ok(())
+}
+
+// Fetch a remote template into `temp`, returning its root.
+fn fetch_into(
+    source: &Source,
+    branch: Option<&str>,
+    temp: &Path,
+) -> Result<PathBuf, failure::Error> {
+    match source {
+        Source::GitHub { owner, repo } => {
+            let mut url = format!("https://api.github.com/repos/{}/{}/tarball", owner, repo);
+            if let Some(branch) = branch {
+                url = format!("{}/{}", url, branch);
+            }
+
+            match install::download(&url)? {
+                Some(data) => unpack(&data, temp),
+                None => failure::bail!(
+                    "There is no template at https://github.com/{}/{}{}",
+                    owner,
+                    repo,
+                    match branch {
+                        Some(branch) => format!(" on branch `{}`", branch),
+                        None => String::new(),
+                    }
+                ),
+            }
+        }
+        Source::Git(url) => {
+            let git = match which("git") {
+                Ok(git) => git,
+                Err(_) => failure::bail!(
+                    "Fetching the template at {} needs git, is it installed?",
+                    url
+                ),
+            };
+
+            let root = temp.join("template");
+            let mut command = Command::new(git);
+            command.args(["clone", "--depth", "1"]);
+            if let Some(branch) = branch {
+                command.args(["--branch", branch]);
+            }
+            command.arg(url).arg(&root);
+
+            commands::run(command, "git clone")?;
+            Ok(root)
+        }
+        Source::Tarball(location) => {
+            let data = if location.starts_with("http://") || location.starts_with("https://") {
+                match install::download(location)? {
+                    Some(data) => data,
+                    None => failure::bail!("There is no template at {}", location),
+                }
+            } else {
+                fs::read(location)?
+            };
+
+            unpack(&data, temp)
+        }
+        Source::Dir(path) => Ok(path.to_path_buf()),
+    }
+}
+
+// Unpack a tarball into `dst`. Tarballs usually hold their files in a single
+// directory, which is then the root of the template.
+fn unpack(data: &[u8], dst: &Path) -> Result<PathBuf, failure::Error> {
+    let mut archive = tar::Archive::new(GzDecoder::new(data));
+    archive
+        .unpack(dst)
+        .map_err(|e| failure::format_err!("failed to unpack the template: {}", e))?;
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dst)? {
+        let entry = entry?;
+        // GitHub tarballs start with a header git archive writes as a file
+        if entry.file_name() != "pax_global_header" {
+            entries.push(entry.path());
+        }
+    }
+
+    match entries.as_slice() {
+        [only] if only.is_dir() => Ok(only.to_path_buf()),
+        _ => Ok(dst.to_path_buf()),
+    }
+}
+
+// Copy `src`, a directory of the template at `root`, to `dst`. Symbolic
+// links are never read through, as a template could otherwise copy any file
+// of the user into the project; links to files of the template are
+// recreated, others are refused.
+fn copy_dir(
+    root: &Path,
+    src: &Path,
+    dst: &Path,
+    filter: &WatchFilter,
+    placeholder: &Regex,
+    values: &HashMap<String, String>,
+) -> Result<(), failure::Error> {
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if file_name == ".git"
+            || file_name == IGNORE_FILE
+            || file_name == MANIFEST_FILE
+            || filter.is_ignored(&path)
+        {
+            continue;
+        }
+
+        let target = dst.join(substitute(&file_name, placeholder, values));
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            let link = fs::read_link(&path)?;
+            if !resolve_link(src, &link).starts_with(root) {
+                failure::bail!(
+                    "The template links `{}` to `{}`, outside of the template",
+                    file_name,
+                    link.display()
+                );
+            }
+            symlink(&link, &target, path.is_dir())?;
+        } else if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            copy_dir(root, &path, &target, filter, placeholder, values)?;
+        } else {
+            match String::from_utf8(fs::read(&path)?) {
+                Ok(text) => {
+                    fs::write(&target, substitute(&text, placeholder, values))?;
+                    fs::set_permissions(&target, entry.metadata()?.permissions())?;
+                }
+                // binary files are copied as is
+                Err(_) => {
+                    fs::copy(&path, &target)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Where a link in `dir` points to, without following any other link.
+fn resolve_link(dir: &Path, link: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in dir.join(link).components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+#[cfg(unix)]
+fn symlink(link: &Path, at: &Path, _is_dir: bool) -> Result<(), failure::Error> {
+    std::os::unix::fs::symlink(link, at)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn symlink(link: &Path, at: &Path, is_dir: bool) -> Result<(), failure::Error> {
+    if is_dir {
+        std::os::windows::fs::symlink_dir(link, at)?;
+    } else {
+        std::os::windows::fs::symlink_file(link, at)?;
+    }
+    Ok(())
+}
+
+// Replace the placeholders, as matched by PLACEHOLDER_PATTERN, with a value,
+// leaving the others as they are.
+pub fn substitute(text: &str, placeholder: &Regex, values: &HashMap<String, String>) -> String {
+    placeholder
+        .replace_all(text, |caps: &Captures| match values.get(&caps["key"]) {
+            Some(value) => value.to_string(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_template_sources() {
+        assert_eq!(
+            Source::parse("https://github.com/cloudflare/worker-template"),
+            Source::GitHub {
+                owner: "cloudflare".to_string(),
+                repo: "worker-template".to_string()
+            }
+        );
+        assert_eq!(
+            Source::parse("github.com/cloudflare/worker-template.git/"),
+            Source::GitHub {
+                owner: "cloudflare".to_string(),
+                repo: "worker-template".to_string()
+            }
+        );
+        assert_eq!(
+            Source::parse("https://gitlab.com/someone/template.git"),
+            Source::Git("https://gitlab.com/someone/template.git".to_string())
+        );
+        assert_eq!(
+            Source::parse("https://example.com/template.tar.gz"),
+            Source::Tarball("https://example.com/template.tar.gz".to_string())
+        );
+        assert_eq!(Source::parse("src"), Source::Dir(PathBuf::from("src")));
+    }
+
+    #[test]
+    fn it_substitutes_known_placeholders() {
+        let mut values = HashMap::new();
+        values.insert("project-name".to_string(), "my-worker".to_string());
+        values.insert("authors".to_string(), "Jo <jo@example.com>".to_string());
+
+        assert_eq!(
+            substitute(
+                "name = \"{{ project-name }}\"\nauthors = [\"{{authors}}\"]\n{{ unknown }}",
+                &Regex::new(PLACEHOLDER_PATTERN).unwrap(),
+                &values
+            ),
+            "name = \"my-worker\"\nauthors = [\"Jo <jo@example.com>\"]\n{{ unknown }}"
+        );
+    }
+
+    #[test]
+    fn it_keeps_subdirs_inside_the_template() {
+        assert!(check_subdir("templates/router").is_ok());
+        assert!(check_subdir("..").is_err());
+        assert!(check_subdir("templates/../../etc").is_err());
+        assert!(check_subdir("/etc").is_err());
+        assert!(check_subdir("./templates").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_only_keeps_links_inside_the_template() {
+        use crate::util::temp_dir_with;
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir_with("template_with_links", &[("src/index.js", "")]);
+        symlink("src/index.js", root.join("main.js")).unwrap();
+        let template = Template {
+            root: root.clone(),
+            temp: None,
+        };
+
+        let dst = env::temp_dir().join("project_with_links");
+        drop(fs::remove_dir_all(&dst));
+        template.instantiate(&dst, &HashMap::new()).unwrap();
+        assert_eq!(
+            fs::read_link(dst.join("main.js")).unwrap(),
+            PathBuf::from("src/index.js")
+        );
+
+        symlink("../../etc/passwd", root.join("src/creds")).unwrap();
+        let dst = env::temp_dir().join("project_with_outside_links");
+        drop(fs::remove_dir_all(&dst));
+        assert!(template.instantiate(&dst, &HashMap::new()).is_err());
+        assert!(!dst.join("src/creds").exists());
+
+        for dir in &[root, dst, env::temp_dir().join("project_with_links")] {
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,169 @@
+mod wizard;
+
+use std::fs;
+use std::path::Path;
+
+use crate::settings::global_user::GlobalUser;
+use crate::settings::project::{Project, ProjectType};
+use crate::terminal::{interactive, message};
+
+pub fn init(name: Option<&str>, project_type: Option<ProjectType>) -> Result<(), failure::Error> {
+    if Path::new("./wrangler.toml").exists() {
+        failure::bail!("A wrangler.toml file already exists! Please remove it before running this command again.");
+    }
+    let dirname = get_current_dirname()?;
+    let name = name.unwrap_or_else(|| &dirname);
+    let project_type = match project_type {
+        Some(project_type) => project_type,
+        None => match detect_project_type(Path::new("./"))? {
+            Some((project_type, reason)) => {
+                message::info(&format!(
+                    "Detected a {} project, as {}",
+                    project_type, reason
+                ));
+                project_type
+            }
+            None => {
+                message::info("Couldn't tell what kind of project this is, making it a webpack one. Pass --type to pick another.");
+                ProjectType::default()
+            }
+        },
+    };
+
+    let mut project = Project::scaffold(name.to_string(), project_type);
+
+    // the ids can be filled in by hand later, so credentials are optional
+    if let Ok(user) = GlobalUser::new() {
+        if interactive::is_interactive()
+            && interactive::confirm(
+                "Fill in your account and zone ids from your Cloudflare account?",
+                true,
+            )?
+        {
+            // a failed lookup still leaves a usable scaffold, ids and all empty
+            let mut filled = project.clone();
+            match wizard::run(&user, &mut filled) {
+                Ok(_) => project = filled,
+                Err(e) => message::warn(&format!(
+                    "Couldn't fill in your ids, leaving them empty: {}",
+                    e
+                )),
+            }
+        }
+    }
+
+    project.write(Path::new("./"))?;
+    message::success("Succesfully created a `wrangler.toml`");
+    Ok(())
+}
+
+// What kind of project `dir` holds, and why wrangler thinks so. A manifest
+// that doesn't parse tells nothing, so detection goes on without it.
+fn detect_project_type(dir: &Path) -> Result<Option<(ProjectType, &'static str)>, failure::Error> {
+    let cargo_toml = dir.join("Cargo.toml");
+    if cargo_toml.is_file() {
+        let manifest: Option<toml::Value> = toml::from_str(&fs::read_to_string(&cargo_toml)?).ok();
+        let is_cdylib = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("lib"))
+            .and_then(|lib| lib.get("crate-type"))
+            .and_then(|crate_type| crate_type.as_array())
+            .map(|crate_types| crate_types.iter().any(|t| t.as_str() == Some("cdylib")))
+            .unwrap_or(false);
+        if is_cdylib {
+            return Ok(Some((ProjectType::Rust, "Cargo.toml builds a cdylib")));
+        }
+    }
+
+    if dir.join("webpack.config.js").is_file() {
+        return Ok(Some((ProjectType::Webpack, "there is a webpack.config.js")));
+    }
+
+    let package_json = dir.join("package.json");
+    if package_json.is_file() {
+        let package: Option<serde_json::Value> =
+            serde_json::from_str(&fs::read_to_string(&package_json)?).ok();
+        let main = package
+            .as_ref()
+            .and_then(|package| package.get("main"))
+            .and_then(|main| main.as_str());
+        if main.is_some() {
+            return Ok(Some((ProjectType::JavaScript, "package.json has a main")));
+        }
+    }
+
+    Ok(None)
+}
+
+fn get_current_dirname() -> Result<String, failure::Error> {
+    let current_path = std::env::current_dir()?;
+    let parent = current_path.parent();
+    let dirname = match parent {
+        Some(parent) => current_path.strip_prefix(parent)?.display().to_string(),
+        None => "worker".to_string(),
+    };
+    Ok(dirname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::temp_dir_with;
+
+    fn detect(name: &str, files: &[(&str, &str)]) -> Option<String> {
+        let dir = temp_dir_with(name, files);
+        let detected = detect_project_type(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        detected.map(|(project_type, _)| project_type.to_string())
+    }
+
+    #[test]
+    fn it_detects_rust_projects_building_a_cdylib() {
+        let cdylib = "[package]\nname = \"worker\"\n\n[lib]\ncrate-type = [\"cdylib\", \"rlib\"]\n";
+        assert_eq!(
+            detect("it_detects_rust_cdylib", &[("Cargo.toml", cdylib)]),
+            Some("rust".to_string())
+        );
+
+        let bin = "[package]\nname = \"tool\"\n";
+        assert_eq!(detect("it_detects_rust_bin", &[("Cargo.toml", bin)]), None);
+    }
+
+    #[test]
+    fn it_detects_webpack_and_javascript_projects() {
+        let package = "{\"name\": \"worker\", \"main\": \"index.js\"}";
+        assert_eq!(
+            detect(
+                "it_detects_webpack",
+                &[("package.json", package), ("webpack.config.js", "")]
+            ),
+            Some("webpack".to_string())
+        );
+        assert_eq!(
+            detect("it_detects_javascript", &[("package.json", package)]),
+            Some("js".to_string())
+        );
+        assert_eq!(
+            detect("it_detects_nothing", &[("package.json", "{}")]),
+            None
+        );
+    }
+
+    #[test]
+    fn it_skips_manifests_that_dont_parse() {
+        assert_eq!(
+            detect(
+                "it_skips_a_broken_cargo_toml",
+                &[
+                    ("Cargo.toml", "[package"),
+                    ("package.json", "{\"main\": \"index.js\"}")
+                ]
+            ),
+            Some("js".to_string())
+        );
+        assert_eq!(
+            detect("it_skips_a_broken_package_json", &[("package.json", "{")]),
+            None
+        );
+    }
+}
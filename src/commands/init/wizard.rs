@@ -0,0 +1,136 @@
+use serde::Deserialize;
+
+use crate::http::{self, API};
+use crate::settings::global_user::GlobalUser;
+use crate::settings::project::Project;
+use crate::terminal::{interactive, message};
+
+// An account or a zone.
+#[derive(Deserialize)]
+struct Resource {
+    id: String,
+    name: String,
+}
+
+// Fill in the account id, zone id and route of `project`, picking from the
+// accounts and zones of `user`.
+pub fn run(user: &GlobalUser, project: &mut Project) -> Result<(), failure::Error> {
+    let client = http::auth_client(user);
+
+    let accounts: Vec<Resource> = http::list(&client, &format!("{}/accounts", API))?;
+    let account = match accounts.len() {
+        0 => failure::bail!("{} has no Cloudflare accounts", user.email),
+        1 => {
+            message::info(&format!("Using your account {}", accounts[0].name));
+            &accounts[0]
+        }
+        _ => {
+            let names: Vec<String> = accounts.iter().map(|a| a.name.clone()).collect();
+            &accounts[interactive::select("Which account is the worker for?", &names)?]
+        }
+    };
+    project.account_id = account.id.clone();
+
+    let zones: Vec<Resource> =
+        http::list(&client, &format!("{}/zones?account.id={}", API, account.id))?;
+    if zones.is_empty() {
+        message::info("The account has no zones, the worker can only be published to workers.dev");
+    } else {
+        let mut choices: Vec<String> = zones.iter().map(|z| z.name.clone()).collect();
+        choices.push("none, only publish to workers.dev".to_string());
+
+        let choice = interactive::select("Which zone should the worker run on?", &choices)?;
+        if let Some(zone) = zones.get(choice) {
+            project.zone_id = Some(zone.id.clone());
+            project.route = Some(ask_route(&zone.name)?);
+        }
+    }
+
+    validate(project)
+}
+
+fn ask_route(zone_name: &str) -> Result<String, failure::Error> {
+    let default = format!("{}/*", zone_name);
+
+    loop {
+        let answer = interactive::ask(&format!(
+            "Which route should the worker run on? [{}]:",
+            default
+        ))?;
+        let route = if answer.is_empty() {
+            default.clone()
+        } else {
+            answer
+        };
+
+        match check_route(&route, zone_name) {
+            Ok(()) => return Ok(route),
+            Err(e) => message::warn(&e.to_string()),
+        }
+    }
+}
+
+// The ids must look like ids, and a route needs a zone to belong to.
+fn validate(project: &Project) -> Result<(), failure::Error> {
+    if !is_id(&project.account_id) {
+        failure::bail!("`{}` is not an account id", project.account_id);
+    }
+
+    let zone_id = project.zone_id.clone().unwrap_or_default();
+    let route = project.route.clone().unwrap_or_default();
+    if !zone_id.is_empty() && !is_id(&zone_id) {
+        failure::bail!("`{}` is not a zone id", zone_id);
+    }
+    if !route.is_empty() && zone_id.is_empty() {
+        failure::bail!("The route `{}` needs a zone_id", route);
+    }
+
+    Ok(())
+}
+
+fn is_id(id: &str) -> bool {
+    id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// A route only matches requests to its zone, such as `example.com/*` or
+// `*.example.com/api/*` for `example.com`.
+fn check_route(route: &str, zone_name: &str) -> Result<(), failure::Error> {
+    let without_scheme = route
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    let host = without_scheme.split('/').next().unwrap_or("");
+    let host = host.trim_start_matches('*').trim_start_matches('.');
+
+    if host == zone_name || host.ends_with(&format!(".{}", zone_name)) {
+        Ok(())
+    } else {
+        failure::bail!(
+            "The route `{}` isn't on {}, it should look like `{}/*`",
+            route,
+            zone_name,
+            zone_name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_checks_routes_belong_to_their_zone() {
+        assert!(check_route("example.com/*", "example.com").is_ok());
+        assert!(check_route("https://api.example.com/v1/*", "example.com").is_ok());
+        assert!(check_route("*.example.com/*", "example.com").is_ok());
+        assert!(check_route("*example.com/*", "example.com").is_ok());
+        assert!(check_route("example.org/*", "example.com").is_err());
+        assert!(check_route("notexample.com/*", "example.com").is_err());
+    }
+
+    #[test]
+    fn it_recognizes_ids() {
+        assert!(is_id("0123456789abcdef0123456789ABCDEF"));
+        assert!(!is_id(""));
+        assert!(!is_id("0123456789abcdef0123456789abcdeg"));
+    }
+}
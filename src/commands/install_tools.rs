@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use crate::commands::build::wranglerjs;
+use crate::install;
+use crate::settings::project::Project;
+use crate::settings::tools::Tools;
+use crate::terminal::message;
+
+/// Download tools to the cache, so that later builds can run offline. Each
+/// of `specs` is a tool name, optionally followed by `@<version>`; without
+/// any, every tool is fetched at its pinned or latest version.
+pub fn install_tools(specs: &[&str]) -> Result<(), failure::Error> {
+    if install::is_offline() {
+        failure::bail!("Can't fetch tools while offline, unset WRANGLER_OFFLINE and try again");
+    }
+
+    // the pins of the project in the current directory, if any
+    let pins = if Path::new("./wrangler.toml").exists() {
+        Tools::for_project(&Project::new()?)?
+    } else {
+        Tools::global()?
+    };
+
+    let specs: Vec<&str> = if specs.is_empty() {
+        install::TOOLS.iter().map(|(name, _)| *name).collect()
+    } else {
+        specs.to_vec()
+    };
+
+    for spec in specs {
+        let (tool_name, version) = parse_spec(spec);
+        let owner = match install::TOOLS.iter().find(|(name, _)| *name == tool_name) {
+            Some((_, owner)) => owner,
+            None => failure::bail!(
+                "wrangler doesn't install `{}`, pick one of: {}",
+                tool_name,
+                install::TOOLS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        };
+        let version = version.or_else(|| pins.version(tool_name));
+
+        if tool_name == "wranglerjs" {
+            // its node dependencies are needed offline too
+            let tools = Tools {
+                wranglerjs: version,
+                ..Tools::default()
+            };
+            let path = wranglerjs::install(&tools)?;
+            message::success(&format!(
+                "wranglerjs {} is installed at {}",
+                wranglerjs::version(&tools),
+                path.display()
+            ));
+        } else {
+            let version = match version {
+                Some(version) => version,
+                None => install::latest_version(tool_name)?,
+            };
+            let download = install::fetch(tool_name, owner, &version)?;
+            message::success(&format!(
+                "{} {} is installed at {}",
+                tool_name,
+                version,
+                download.path().display()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// `wasm-pack@0.8.1` is version 0.8.1 of wasm-pack.
+fn parse_spec(spec: &str) -> (&str, Option<String>) {
+    let mut parts = spec.splitn(2, '@');
+    let tool_name = parts.next().unwrap_or(spec);
+    let version = parts
+        .next()
+        .map(|version| version.trim_start_matches('v').to_string());
+
+    (tool_name, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_tool_versions() {
+        assert_eq!(parse_spec("wasm-pack"), ("wasm-pack", None));
+        assert_eq!(
+            parse_spec("wasm-pack@v0.8.1"),
+            ("wasm-pack", Some("0.8.1".to_string()))
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use console::style;
+use serde::Deserialize;
+
+use crate::http::{self, API};
+use crate::settings::global_user::GlobalUser;
+use crate::settings::project::migrate::{self, Context};
+use crate::terminal::{interactive, message};
+
+// Unchanged lines shown around a change.
+const CONTEXT_LINES: usize = 2;
+
+#[derive(Deserialize)]
+struct Namespace {
+    id: String,
+    title: String,
+}
+
+// Rewrite the wrangler.toml of the current directory to the current schema,
+// once `yes` or confirmed.
+pub fn migrate(yes: bool) -> Result<(), failure::Error> {
+    let config_path = Path::new("./wrangler.toml");
+    if !config_path.is_file() {
+        failure::bail!("There is no wrangler.toml here to migrate");
+    }
+    let source = fs::read_to_string(config_path)?;
+
+    // only asked for when a namespace needs its id
+    let account_id = account_id(&source);
+    let namespaces: RefCell<Option<HashMap<String, String>>> = RefCell::new(None);
+    let kv_namespace_id = |title: &str| {
+        let mut namespaces = namespaces.borrow_mut();
+        namespaces
+            .get_or_insert_with(|| kv_namespaces(&account_id))
+            .get(title)
+            .cloned()
+    };
+
+    let migrated = migrate::migrate(
+        &source,
+        &Context {
+            kv_namespace_id: &kv_namespace_id,
+        },
+    )?;
+    if migrated.applied.is_empty() {
+        message::success("Your wrangler.toml is up to date");
+        return Ok(());
+    }
+
+    for migration in &migrated.applied {
+        message::info(&format!(
+            "As of {} {}",
+            migration.version, migration.description
+        ));
+    }
+    message::info(&format!(
+        "Your wrangler.toml would change like this:\n\n{}\n",
+        diff(&source, &migrated.toml).join("\n")
+    ));
+
+    if !yes {
+        if !interactive::is_interactive() {
+            failure::bail!("Pass `--yes` to update your wrangler.toml without a terminal");
+        }
+        if !interactive::confirm("Update your wrangler.toml?", true)? {
+            failure::bail!("Left your wrangler.toml as it was");
+        }
+    }
+
+    fs::write(config_path, &migrated.toml)?;
+    for note in &migrated.notes {
+        message::warn(note);
+    }
+    message::success("Updated your wrangler.toml");
+    Ok(())
+}
+
+fn account_id(source: &str) -> String {
+    let toml: Option<toml::Value> = toml::from_str(source).ok();
+    toml.as_ref()
+        .and_then(|toml| toml.get("account_id"))
+        .and_then(|id| id.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+// The ids of the account's namespaces by title. Without them the ids are
+// left to fill in, so failing to list them is only a warning.
+fn kv_namespaces(account_id: &str) -> HashMap<String, String> {
+    let user = match GlobalUser::new() {
+        Ok(user) if !account_id.is_empty() => user,
+        _ => return HashMap::new(),
+    };
+
+    let url = format!("{}/accounts/{}/storage/kv/namespaces", API, account_id);
+    match http::list::<Namespace>(&http::auth_client(&user), &url) {
+        Ok(namespaces) => namespaces
+            .into_iter()
+            .map(|namespace| (namespace.title, namespace.id))
+            .collect(),
+        Err(e) => {
+            message::warn(&format!("Couldn't look up your KV namespaces: {}", e));
+            HashMap::new()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl<'a> Change<'a> {
+    fn marker(&self) -> char {
+        match self {
+            Change::Same(_) => ' ',
+            Change::Removed(_) => '-',
+            Change::Added(_) => '+',
+        }
+    }
+
+    fn line(&self) -> &'a str {
+        match self {
+            Change::Same(line) | Change::Removed(line) | Change::Added(line) => line,
+        }
+    }
+}
+
+// The lines of `old` and `new`, as the fewest lines removed and added.
+fn changes<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // the longest common subsequence of the lines after i and j
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+// The changed lines, and a few unchanged ones around them.
+fn diff(old: &str, new: &str) -> Vec<String> {
+    let changes = changes(old, new);
+    let near_change = |i: usize| {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + CONTEXT_LINES + 1).min(changes.len());
+        changes[start..end]
+            .iter()
+            .any(|change| change.marker() != ' ')
+    };
+
+    let mut lines = Vec::new();
+    let mut skipped = false;
+    for (i, change) in changes.iter().enumerate() {
+        if !near_change(i) {
+            skipped = true;
+            continue;
+        }
+        if skipped && !lines.is_empty() {
+            lines.push(style("  ...").dim().to_string());
+        }
+        skipped = false;
+
+        let line = format!("{} {}", change.marker(), change.line());
+        lines.push(match change {
+            Change::Same(_) => line,
+            Change::Removed(_) => style(line).red().to_string(),
+            Change::Added(_) => style(line).green().to_string(),
+        });
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_the_fewest_changed_lines() {
+        assert_eq!(
+            changes("a\nb\nc\nd\n", "a\nc\nd\ne\n"),
+            vec![
+                Change::Same("a"),
+                Change::Removed("b"),
+                Change::Same("c"),
+                Change::Same("d"),
+                Change::Added("e"),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_shows_changes_with_a_few_lines_around_them() {
+        console::set_colors_enabled(false);
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "1\n2\n3\n4\n5\n6\n7\n8\nnine\n";
+
+        assert_eq!(diff(old, new), vec!["  7", "  8", "- 9", "+ nine"]);
+        assert_eq!(
+            diff("a\nb\nc\nd\ne\nf\ng\nh\n", "A\nb\nc\nd\ne\nf\ng\nH\n"),
+            vec!["- a", "+ A", "  b", "  c", "  ...", "  f", "  g", "- h", "+ H"]
+        );
+    }
+}
@@ -3,22 +3,40 @@ use std::process::Command;
 use log::info;
 
 pub mod build;
+pub mod cache;
 pub mod config;
 pub mod generate;
 pub mod init;
+pub mod install_tools;
+pub mod migrate;
 pub mod publish;
+pub mod self_uninstall;
+pub mod self_update;
 pub mod subdomain;
+pub mod symbolicate;
+pub mod test;
+pub mod validate;
 pub mod whoami;
 
 pub use self::config::global_config;
 pub use build::build;
+pub use build::build_and_analyze;
+pub use build::build_and_watch;
+pub use build::print_tool_versions;
 pub use build::watch_and_build;
 pub use generate::generate;
 pub use init::init;
+pub use install_tools::install_tools;
+pub use migrate::migrate;
 pub use publish::preview::preview;
 pub use publish::preview::HTTPMethod;
 pub use publish::publish;
+pub use self_uninstall::self_uninstall;
+pub use self_update::self_update;
 pub use subdomain::subdomain;
+pub use symbolicate::symbolicate;
+pub use test::test;
+pub use validate::validate;
 pub use whoami::whoami;
 
 /// Run the given command and return its stdout.
@@ -1,5 +1,5 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde::{self, Deserialize};
 
@@ -15,18 +15,93 @@ struct KrateManifest {
 
 impl Krate {
     pub fn new(krate_path: &str) -> Result<Krate, failure::Error> {
-        let manifest_path = Path::new(krate_path).join("Cargo.toml");
-        if !manifest_path.is_file() {
-            failure::bail!(
-                "crate directory is missing a `Cargo.toml` file; is `{}` the \
-                 wrong directory?",
-                krate_path
-            )
-        }
-
-        let cargo_toml: String = fs::read_to_string(manifest_path)?.parse()?;
+        let cargo_toml = read_manifest(krate_path)?;
         let krate: KrateManifest = toml::from_str(&cargo_toml)?;
 
         Ok(krate.package)
     }
+
+    // The local `path` dependencies of the crate at {krate_path}, including
+    // build and target-specific dependencies.
+    pub fn path_dependencies(krate_path: &str) -> Result<Vec<PathBuf>, failure::Error> {
+        let cargo_toml = read_manifest(krate_path)?;
+        let manifest: toml::Value = toml::from_str(&cargo_toml)?;
+
+        Ok(find_path_dependencies(&manifest)
+            .into_iter()
+            .map(|path| Path::new(krate_path).join(path))
+            .collect())
+    }
+}
+
+fn read_manifest(krate_path: &str) -> Result<String, failure::Error> {
+    let manifest_path = Path::new(krate_path).join("Cargo.toml");
+    if !manifest_path.is_file() {
+        failure::bail!(
+            "crate directory is missing a `Cargo.toml` file; is `{}` the \
+             wrong directory?",
+            krate_path
+        )
+    }
+
+    Ok(fs::read_to_string(manifest_path)?.parse()?)
+}
+
+fn find_path_dependencies(manifest: &toml::Value) -> Vec<String> {
+    let mut tables = Vec::new();
+    for key in &["dependencies", "build-dependencies"] {
+        tables.extend(manifest.get(key));
+    }
+    if let Some(targets) = manifest.get("target").and_then(|t| t.as_table()) {
+        for target in targets.values() {
+            for key in &["dependencies", "build-dependencies"] {
+                tables.extend(target.get(key));
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    for table in tables.iter().filter_map(|t| t.as_table()) {
+        for dependency in table.values() {
+            if let Some(path) = dependency.get("path").and_then(|p| p.as_str()) {
+                if !paths.iter().any(|p| p == path) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_path_dependencies() {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+            [package]
+            name = "worker"
+
+            [dependencies]
+            wasm-bindgen = "0.2"
+            utils = { path = "../utils" }
+
+            [build-dependencies]
+            codegen = { path = "codegen", version = "0.1" }
+
+            [target.'cfg(target_arch = "wasm32")'.dependencies]
+            utils = { path = "../utils" }
+            wasm-only = { path = "../wasm-only" }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_path_dependencies(&manifest),
+            vec!["../utils", "codegen", "../wasm-only"]
+        );
+    }
 }
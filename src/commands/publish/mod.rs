@@ -1,4 +1,4 @@
-mod krate;
+pub mod krate;
 pub mod package;
 pub mod preview;
 mod route;
@@ -6,23 +6,52 @@ mod upload_form;
 
 pub use package::Package;
 use route::Route;
-use upload_form::build_script_upload_form;
+use upload_form::{build_bundle_upload_form, build_script_upload_form};
+pub use upload_form::{check_project_size, print_built_size};
 
 use log::info;
+use reqwest::multipart::Form;
 
 use crate::commands;
+use crate::commands::build::wranglerjs::Bundle;
 use crate::commands::subdomain::Subdomain;
 use crate::http;
 use crate::settings::global_user::GlobalUser;
-use crate::settings::project::Project;
+use crate::settings::project::{Project, ProjectType};
 use crate::terminal::message;
 
-pub fn publish(user: &GlobalUser, project: &Project, release: bool) -> Result<(), failure::Error> {
+pub fn publish(
+    user: &GlobalUser,
+    project: &Project,
+    release: bool,
+    ignore_size_limit: bool,
+) -> Result<(), failure::Error> {
     info!("release = {}", release);
 
     validate_project(project, release)?;
     commands::build(&project)?;
-    publish_script(&user, &project, release)?;
+    check_project_size(project, ignore_size_limit)?;
+
+    let named_bundles = match project.project_type {
+        ProjectType::Webpack => Bundle::new().named_bundles()?,
+        _ => None,
+    };
+
+    match named_bundles {
+        // every webpack configuration is published as its own worker
+        Some(named_bundles) => {
+            for (name, bundle) in named_bundles {
+                let script_name = format!("{}-{}", project.name, name);
+                let script_upload_form = build_bundle_upload_form(project, &bundle)?;
+                publish_script(&user, &project, &script_name, script_upload_form, release)?;
+            }
+        }
+        None => {
+            let script_upload_form = build_script_upload_form(project)?;
+            publish_script(&user, &project, &project.name, script_upload_form, release)?;
+        }
+    }
+
     if release {
         info!("release mode detected, making a route...");
         let route = Route::new(&project)?;
@@ -41,24 +70,27 @@ pub fn publish(user: &GlobalUser, project: &Project, release: bool) -> Result<()
 fn publish_script(
     user: &GlobalUser,
     project: &Project,
+    script_name: &str,
+    script_upload_form: Form,
     release: bool,
 ) -> Result<(), failure::Error> {
     let worker_addr = format!(
         "https://api.cloudflare.com/client/v4/accounts/{}/workers/scripts/{}",
-        project.account_id, project.name,
+        project.account_id, script_name,
     );
 
     let client = http::auth_client(user);
 
-    let script_upload_form = build_script_upload_form(project)?;
-
     let mut res = client
         .put(&worker_addr)
         .multipart(script_upload_form)
         .send()?;
 
     if res.status().is_success() {
-        message::success("Successfully published your script.");
+        message::success(&format!(
+            "Successfully published your script `{}`.",
+            script_name
+        ));
     } else {
         failure::bail!(
             "Something went wrong! Status: {}, Details {}",
@@ -71,7 +103,7 @@ fn publish_script(
         let private = project.private.unwrap_or(false);
         if !private {
             info!("--release not passed, publishing to subdomain");
-            make_public_on_subdomain(project, user)?;
+            make_public_on_subdomain(project, script_name, user)?;
         }
     }
 
@@ -82,13 +114,17 @@ fn build_subdomain_request() -> String {
     serde_json::json!({ "enabled":true}).to_string()
 }
 
-fn make_public_on_subdomain(project: &Project, user: &GlobalUser) -> Result<(), failure::Error> {
+fn make_public_on_subdomain(
+    project: &Project,
+    script_name: &str,
+    user: &GlobalUser,
+) -> Result<(), failure::Error> {
     info!("checking that subdomain is registered");
     let subdomain = Subdomain::get(&project.account_id, user)?;
 
     let sd_worker_addr = format!(
         "https://api.cloudflare.com/client/v4/accounts/{}/workers/scripts/{}/subdomain",
-        project.account_id, project.name,
+        project.account_id, script_name,
     );
 
     let client = http::auth_client(user);
@@ -103,7 +139,7 @@ fn make_public_on_subdomain(project: &Project, user: &GlobalUser) -> Result<(),
     if res.status().is_success() {
         let msg = format!(
             "Successfully made your script available at https://{}.{}.workers.dev",
-            project.name, subdomain
+            script_name, subdomain
         );
         message::success(&msg)
     } else {
@@ -154,6 +190,10 @@ fn validate_project(project: &Project, release: bool) -> Result<(), failure::Err
         if project.route.as_ref().unwrap_or(&"".to_string()).is_empty() {
             missing_fields.push("route")
         };
+        // a route points to a single worker
+        if project.webpack_config_select == Some("all".to_string()) {
+            failure::bail!("Building every webpack configuration publishes multiple workers, which cannot be released to a single route. Select one with `webpack_config_select` in wrangler.toml.");
+        }
         //zoned deploy destination
         "a route"
     } else {
@@ -1,12 +1,25 @@
+use crate::commands::symbolicate::Symbolicator;
 use crate::terminal::message;
+use chrono::Local;
 use log::info;
-use serde::Serialize;
-use ws::{CloseCode, Handler, Handshake, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use ws::{CloseCode, Handler, Handshake, Message, Sender};
 
-//for now, this is only used by livereloading.
-//in the future we may use this websocket for other things
-//so support other message types
-#[derive(Debug, Serialize)]
+#[cfg(not(debug_assertions))]
+const SAFE_ORIGINS: &[&str] = &["https://cloudflareworkers.com"];
+
+// debug builds also trust a preview page served locally, on any port
+#[cfg(debug_assertions)]
+const SAFE_ORIGINS: &[&str] = &["https://cloudflareworkers.com", "http://localhost"];
+
+const SAFE_ADDRS: &[&str] = &["127.0.0.1", "localhost", "::1"];
+
+//messages flow both ways over this websocket: wrangler tells the preview
+//page to livereload, and the preview page reports what the worker is doing
+//(console output, uncaught exceptions and requests it served).
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FiddleMessage {
     pub session_id: String,
@@ -14,53 +27,176 @@ pub struct FiddleMessage {
     pub data: FiddleMessageData,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum FiddleMessageData {
     #[serde(rename_all = "camelCase")]
     LiveReload { new_id: String },
+    #[serde(rename_all = "camelCase")]
+    Console { level: String, messages: Vec<Value> },
+    #[serde(rename_all = "camelCase")]
+    Exception {
+        message: String,
+        stack: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Request {
+        method: String,
+        url: String,
+        status: u16,
+        duration: Option<u64>,
+    },
+}
+
+impl FiddleMessageData {
+    // Print a message sent by the preview page, prefixed with the time it
+    // arrived.
+    fn log(&self) {
+        let timestamp = Local::now().format("%H:%M:%S");
+        let msg = format!("[{}] {}", timestamp, self.summary());
+
+        match self {
+            FiddleMessageData::Console { level, .. } if level == "error" => {
+                message::user_error(&msg)
+            }
+            FiddleMessageData::Console { level, .. } if level == "warn" => message::warn(&msg),
+            FiddleMessageData::Exception { stack, .. } => match stack {
+                Some(stack) => message::user_error(&format!("{}\n{}", msg, stack)),
+                None => message::user_error(&msg),
+            },
+            _ => message::preview(&msg),
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            FiddleMessageData::LiveReload { new_id } => format!("livereload {}", new_id),
+            FiddleMessageData::Console { level, messages } => {
+                let messages: Vec<String> = messages
+                    .iter()
+                    .map(|m| match m {
+                        Value::String(s) => s.to_string(),
+                        _ => m.to_string(),
+                    })
+                    .collect();
+                format!("console.{}: {}", level, messages.join(" "))
+            }
+            FiddleMessageData::Exception { message, .. } => {
+                format!("Uncaught exception: {}", message)
+            }
+            FiddleMessageData::Request {
+                method,
+                url,
+                status,
+                duration,
+            } => match duration {
+                Some(duration) => format!("{} {} {} ({}ms)", method, url, status, duration),
+                None => format!("{} {} {}", method, url, status),
+            },
+        }
+    }
 }
 
 pub struct FiddleMessageServer {
     pub out: Sender,
+    pub session_id: String,
+    // origins trusted on top of the built-in SAFE_ORIGINS
+    pub allowed_origins: Vec<String>,
+    // addresses trusted on top of the built-in SAFE_ADDRS, such as the
+    // address ports are forwarded from into a container
+    pub allowed_addresses: Vec<String>,
+    // set once the connection has passed the origin and address checks
+    pub trusted: bool,
+    // maps the stack of uncaught exceptions back to the original sources
+    pub source_map: Option<PathBuf>,
 }
 
-impl Handler for FiddleMessageServer {
-    fn on_open(&mut self, handshake: Handshake) -> ws::Result<()> {
-        #[cfg(not(debug_assertions))]
-        const SAFE_ORIGINS: &[&str] = &["https://cloudflareworkers.com"];
+impl FiddleMessageServer {
+    // The source map is loaded for every exception, as each rebuild replaces it.
+    fn symbolicate(&self, data: FiddleMessageData) -> FiddleMessageData {
+        match (data, &self.source_map) {
+            (
+                FiddleMessageData::Exception {
+                    message,
+                    stack: Some(stack),
+                },
+                Some(source_map),
+            ) => {
+                let stack = match Symbolicator::load(source_map) {
+                    Ok(Some(symbolicator)) => symbolicator.symbolicate(&stack),
+                    _ => stack,
+                };
 
-        #[cfg(debug_assertions)]
-        const SAFE_ORIGINS: &[&str] = &["https://cloudflareworkers.com", "http://localhost"];
+                FiddleMessageData::Exception {
+                    message,
+                    stack: Some(stack),
+                }
+            }
+            (data, _) => data,
+        }
+    }
+}
+
+// Origins are compared exactly, scheme, host and port, but for a trailing
+// slash.
+fn normalize_origin(origin: &str) -> &str {
+    origin.trim_end_matches('/')
+}
 
-        const SAFE_ADDRS: &[&str] = &["127.0.0.1", "localhost", "::1"];
+// The origin without its port, if it has one.
+fn origin_without_port(origin: &str) -> &str {
+    let host_start = origin.find("://").map_or(0, |i| i + 3);
+    match origin.rfind(':') {
+        Some(i) if i > host_start => {
+            let port = &origin[i + 1..];
+            if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) {
+                &origin[..i]
+            } else {
+                origin
+            }
+        }
+        _ => origin,
+    }
+}
+
+// The built-in origins match on scheme and host, whatever the port, while
+// the allowed ones must match exactly.
+fn origin_is_safe(origin: &str, allowed_origins: &[String]) -> bool {
+    SAFE_ORIGINS.contains(&origin_without_port(origin))
+        || allowed_origins
+            .iter()
+            .any(|allowed_origin| origin == normalize_origin(allowed_origin))
+}
+
+fn addr_is_safe(addr: &str, allowed_addresses: &[String]) -> bool {
+    SAFE_ADDRS.contains(&addr)
+        || allowed_addresses
+            .iter()
+            .any(|allowed_address| addr == allowed_address)
+}
 
+impl Handler for FiddleMessageServer {
+    fn on_open(&mut self, handshake: Handshake) -> ws::Result<()> {
         //origin() returns Result<Option<&str>>
-        let origin = handshake
-            .request
-            .origin()?
-            .unwrap_or("unknown")
-            .trim_end_matches(|c: char| c == '/' || c == ':' || c.is_numeric());
+        let origin = normalize_origin(handshake.request.origin()?.unwrap_or("unknown"));
 
         //remote_addr returns Result<Option<String>>
         let incoming_addr = handshake.remote_addr()?;
         let incoming_addr = incoming_addr.as_ref().map_or("unknown", String::as_str);
 
-        //only allow connections from cloudflareworkers.com
-        let origin_is_safe = SAFE_ORIGINS
-            .iter()
-            .any(|safe_origin| &origin == safe_origin);
+        //only allow connections from cloudflareworkers.com, or origins allowed in wrangler.toml
+        let origin_is_safe = origin_is_safe(origin, &self.allowed_origins);
 
-        //only allow incoming websocket connections from localhost/current machine.
-        let addr_is_safe = SAFE_ADDRS
-            .iter()
-            .any(|safe_addr| &incoming_addr == safe_addr);
+        //only allow incoming websocket connections from localhost/current machine,
+        //or addresses allowed in wrangler.toml
+        let addr_is_safe = addr_is_safe(incoming_addr, &self.allowed_addresses);
 
         if origin_is_safe && addr_is_safe {
             info!(
                 "Accepted connection from site {} incoming from {}",
                 origin, incoming_addr
             );
+            self.trusted = true;
         } else {
             if !origin_is_safe {
                 message::user_error(&format!(
@@ -83,4 +219,124 @@ impl Handler for FiddleMessageServer {
 
         Ok(())
     }
+
+    fn on_message(&mut self, msg: Message) -> ws::Result<()> {
+        if !self.trusted {
+            return Ok(());
+        }
+
+        if let Message::Text(text) = msg {
+            match serde_json::from_str::<FiddleMessage>(&text) {
+                Ok(msg) => {
+                    if msg.session_id == self.session_id {
+                        self.symbolicate(msg.data).log();
+                    } else {
+                        info!("Ignoring message for session {}", msg.session_id);
+                    }
+                }
+                Err(e) => info!("Ignoring malformed message from preview: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_normalizes_origins() {
+        assert_eq!(
+            normalize_origin("http://localhost:8080/"),
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            normalize_origin("https://cloudflareworkers.com"),
+            "https://cloudflareworkers.com"
+        );
+    }
+
+    #[test]
+    fn it_compares_origins_exactly() {
+        let allowed = vec![
+            "http://10.0.0.1/".to_string(),
+            "http://localhost:3000".to_string(),
+        ];
+
+        assert!(origin_is_safe("https://cloudflareworkers.com", &[]));
+        assert!(origin_is_safe("http://10.0.0.1", &allowed));
+        assert!(origin_is_safe("http://localhost:3000", &allowed));
+        assert!(!origin_is_safe("http://10.0.0.12", &allowed));
+        assert!(!origin_is_safe("http://10.0.0.1:3000", &allowed));
+        assert!(!origin_is_safe(
+            "https://cloudflareworkers.com.evil",
+            &allowed
+        ));
+    }
+
+    #[test]
+    fn it_ignores_the_port_of_built_in_origins() {
+        assert_eq!(
+            origin_without_port("http://localhost:8080"),
+            "http://localhost"
+        );
+        assert_eq!(
+            origin_without_port("https://cloudflareworkers.com"),
+            "https://cloudflareworkers.com"
+        );
+        assert!(origin_is_safe("https://cloudflareworkers.com:443", &[]));
+        assert!(!origin_is_safe("https://cloudflareworkers.com:evil", &[]));
+        if cfg!(debug_assertions) {
+            assert!(origin_is_safe("http://localhost:8787", &[]));
+        }
+    }
+
+    #[test]
+    fn it_only_trusts_local_or_allowed_addresses() {
+        let allowed = vec!["172.17.0.1".to_string()];
+
+        assert!(addr_is_safe("127.0.0.1", &[]));
+        assert!(addr_is_safe("::1", &[]));
+        assert!(addr_is_safe("172.17.0.1", &allowed));
+        assert!(!addr_is_safe("172.17.0.12", &allowed));
+        assert!(!addr_is_safe("10.0.0.1", &allowed));
+    }
+
+    #[test]
+    fn it_reads_console_messages() {
+        let msg: FiddleMessage = serde_json::from_str(
+            r#"{"sessionId":"abc","type":"Console","level":"log","messages":["hello",{"a":1},2]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(msg.session_id, "abc");
+        assert_eq!(msg.data.summary(), r#"console.log: hello {"a":1} 2"#);
+    }
+
+    #[test]
+    fn it_reads_request_summaries() {
+        let msg: FiddleMessage = serde_json::from_str(
+            r#"{"sessionId":"abc","type":"Request","method":"GET","url":"https://example.com/","status":200,"duration":12}"#,
+        )
+        .unwrap();
+
+        assert_eq!(msg.data.summary(), "GET https://example.com/ 200 (12ms)");
+    }
+
+    #[test]
+    fn it_writes_livereload_messages() {
+        let msg = FiddleMessage {
+            session_id: "abc".to_string(),
+            data: FiddleMessageData::LiveReload {
+                new_id: "123".to_string(),
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"sessionId":"abc","type":"LiveReload","newId":"123"}"#
+        );
+    }
 }
@@ -7,9 +7,10 @@ mod http_method;
 pub use http_method::HTTPMethod;
 
 mod upload;
-use upload::upload_and_get_id;
+pub use upload::upload_and_get_id;
 
 use crate::commands;
+use crate::commands::build::wranglerjs::Bundle;
 
 use uuid::Uuid;
 
@@ -17,15 +18,17 @@ use log::info;
 
 use crate::http;
 use crate::settings::global_user::GlobalUser;
-use crate::settings::project::Project;
+use crate::settings::project::{Project, ProjectType};
 use crate::terminal::message;
 
+use std::path::PathBuf;
 use std::sync::mpsc::channel;
 use std::thread;
 use ws::{Sender, WebSocket};
 
 // Using this instead of just `https://cloudflareworkers.com` returns just the worker response to the CLI
-const PREVIEW_ADDRESS: &str = "https://00000000000000000000000000000000.cloudflareworkers.com";
+pub const PREVIEW_ADDRESS: &str = "https://00000000000000000000000000000000.cloudflareworkers.com";
+const PREVIEW_HOST: &str = "example.com";
 
 pub fn preview(
     project: Project,
@@ -38,16 +41,33 @@ pub fn preview(
     let script_id = upload_and_get_id(&project, user.as_ref())?;
 
     let session = Uuid::new_v4().to_simple();
-    let preview_host = "example.com";
+    let preview_host = PREVIEW_HOST;
     let https = true;
     let https_str = if https { "https://" } else { "http://" };
 
     if livereload {
-        let server = WebSocket::new(|out| FiddleMessageServer { out })?.bind("127.0.0.1:0")?; //explicitly use 127.0.0.1, since localhost can resolve to 2 addresses
+        let livereload = project.livereload();
+        let session_id = session.to_string();
+        let allowed_origins = livereload.allowed_origins();
+        let allowed_addresses = livereload.allowed_addresses();
+        let source_map = preview_source_map(&project)?;
+        let server = WebSocket::new(move |out| FiddleMessageServer {
+            out,
+            session_id: session_id.clone(),
+            allowed_origins: allowed_origins.clone(),
+            allowed_addresses: allowed_addresses.clone(),
+            trusted: false,
+            source_map: source_map.clone(),
+        })?
+        .bind((livereload.address().as_str(), livereload.port()))?;
 
         let ws_port = server.local_addr()?.port();
 
-        info!("Opened websocket server on port {}", ws_port);
+        info!(
+            "Opened websocket server on {}:{}",
+            livereload.address(),
+            ws_port
+        );
 
         open_browser(&format!(
             "https://cloudflareworkers.com/?wrangler_session_id={0}&wrangler_ws_port={1}&hide_editor#{2}:{3}{4}",
@@ -65,10 +85,7 @@ pub fn preview(
             script_id, https_str, preview_host
         ))?;
 
-        let cookie = format!(
-            "__ew_fiddle_preview={}{}{}{}",
-            script_id, session, https as u8, preview_host
-        );
+        let cookie = cookie(&script_id, &session.to_string());
 
         let client = http::client();
 
@@ -83,6 +100,35 @@ pub fn preview(
     Ok(())
 }
 
+// The source map of the script being previewed, for webpack projects.
+fn preview_source_map(project: &Project) -> Result<Option<PathBuf>, failure::Error> {
+    match project.project_type {
+        ProjectType::Webpack => {
+            let bundle = Bundle::new();
+            // like the upload, use the first bundle when there are several
+            let source_map = match bundle.named_bundles()? {
+                Some(named_bundles) => named_bundles
+                    .into_iter()
+                    .next()
+                    .map(|(_, bundle)| bundle.source_map_path()),
+                None => Some(bundle.source_map_path()),
+            };
+
+            Ok(source_map.map(PathBuf::from))
+        }
+        _ => Ok(None),
+    }
+}
+
+// The preview service routes requests carrying this cookie to the given script.
+pub fn cookie(script_id: &str, session: &str) -> String {
+    let https = true;
+    format!(
+        "__ew_fiddle_preview={}{}{}{}",
+        script_id, session, https as u8, PREVIEW_HOST
+    )
+}
+
 fn open_browser(url: &str) -> Result<(), failure::Error> {
     let _output = if cfg!(target_os = "windows") {
         let url_escaped = url.replace("&", "^&");
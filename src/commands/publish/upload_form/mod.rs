@@ -1,43 +1,127 @@
 mod project_assets;
+mod project_size;
+mod text_blob;
 mod wasm_module;
 
 use log::info;
 
 use reqwest::multipart::{Form, Part};
-use std::fs;
 use std::path::Path;
 
-use crate::commands::build::wranglerjs;
+use crate::commands::build::{self, wranglerjs};
 use crate::settings::binding;
 use crate::settings::metadata::Metadata;
 use crate::settings::project::kv_namespace;
 use crate::settings::project::{Project, ProjectType};
+use crate::terminal::message;
 
 use project_assets::ProjectAssets;
+use project_size::ProjectSize;
+use text_blob::TextBlob;
 use wasm_module::WasmModule;
 
 use super::{krate, Package};
 
 pub fn build_script_upload_form(project: &Project) -> Result<Form, failure::Error> {
+    let assets = project_assets(project)?;
+
+    build_form(&assets)
+}
+
+// Build the upload form for a single webpack bundle, as built by wranglerjs.
+pub fn build_bundle_upload_form(
+    project: &Project,
+    bundle: &wranglerjs::Bundle,
+) -> Result<Form, failure::Error> {
+    let assets = bundle_assets(project, bundle)?;
+
+    build_form(&assets)
+}
+
+// Print the gzipped size of what the project uploads, per worker.
+// Print the size of a finished build, where it can be told without preparing
+// the upload: Rust projects only get their script generated on publish, and
+// JavaScript projects have nothing built to measure.
+pub fn print_built_size(project: &Project) -> Result<(), failure::Error> {
+    match project.project_type {
+        ProjectType::Rust | ProjectType::JavaScript => Ok(()),
+        ProjectType::Webpack | ProjectType::Custom => print_project_size(project),
+    }
+}
+
+fn print_project_size(project: &Project) -> Result<(), failure::Error> {
+    let limits = project.limits();
+
+    for (name, assets) in worker_assets(project)? {
+        let size = ProjectSize::new(&assets, &limits)?;
+        size.print(&name);
+    }
+
+    Ok(())
+}
+
+// Print the size of each worker the project uploads, and refuse to upload one
+// over the size limit, unless told to ignore it.
+pub fn check_project_size(
+    project: &Project,
+    ignore_size_limit: bool,
+) -> Result<(), failure::Error> {
+    let limits = project.limits();
+
+    for (name, assets) in worker_assets(project)? {
+        let size = ProjectSize::new(&assets, &limits)?;
+        size.print(&name);
+        if !size.is_over_limit() {
+            continue;
+        }
+
+        let worker = match &name {
+            Some(name) => format!("`{}`", name),
+            None => "Your built project".to_string(),
+        };
+        let msg = format!(
+            "{} is {} gzipped, over the size limit of {}.",
+            worker,
+            size.human_size(),
+            size.human_limit()
+        );
+
+        if ignore_size_limit {
+            message::warn(&format!("{} Publishing anyway.", msg));
+        } else {
+            failure::bail!(
+                "{} Pass --ignore-size-limit to publish anyway, or raise `max_size` in the [limits] section of wrangler.toml.",
+                msg
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// The files and bindings the project uploads. For a webpack build of every
+// configuration, those of the first one.
+fn project_assets(project: &Project) -> Result<ProjectAssets, failure::Error> {
     let project_type = &project.project_type;
     let kv_namespaces = project.kv_namespaces();
     match project_type {
         ProjectType::Rust => {
             info!("Rust project detected. Publishing...");
             let name = krate::Krate::new("./")?.name.replace("-", "_");
+            let out_dir = project.build().rust().out_dir();
             // TODO: move into build?
-            build_generated_dir()?;
-            concat_js(&name)?;
+            build::generate_rust_script(&name, &out_dir)?;
 
-            let path = format!("./pkg/{}_bg.wasm", name).to_string();
+            let path = Path::new(&out_dir)
+                .join(format!("{}_bg.wasm", name))
+                .to_string_lossy()
+                .to_string();
             let binding = "wasm".to_string();
             let wasm_module = WasmModule::new(path, binding)?;
 
             let script_path = "./worker/generated/script.js".to_string();
 
-            let assets = ProjectAssets::new(script_path, vec![wasm_module], kv_namespaces)?;
-
-            build_form(&assets)
+            ProjectAssets::new(script_path, vec![wasm_module], Vec::new(), kv_namespaces)
         }
         ProjectType::JavaScript => {
             info!("JavaScript project detected. Publishing...");
@@ -45,31 +129,84 @@ pub fn build_script_upload_form(project: &Project) -> Result<Form, failure::Erro
 
             let script_path = package.main()?;
 
-            let assets = ProjectAssets::new(script_path, Vec::new(), kv_namespaces)?;
-
-            build_form(&assets)
+            ProjectAssets::new(script_path, Vec::new(), Vec::new(), kv_namespaces)
         }
         ProjectType::Webpack => {
             info!("Webpack project detected. Publishing...");
             // FIXME(sven): shouldn't new
             let bundle = wranglerjs::Bundle::new();
 
-            let script_path = bundle.script_path();
+            match bundle.named_bundles()? {
+                Some(named_bundles) => {
+                    let (name, bundle) = named_bundles.into_iter().next().ok_or_else(|| {
+                        failure::format_err!("the last build did not produce any bundle")
+                    })?;
+                    message::warn(&format!(
+                        "Your webpack configuration builds multiple workers, using `{}`",
+                        name
+                    ));
+                    bundle_assets(project, &bundle)
+                }
+                None => bundle_assets(project, &bundle),
+            }
+        }
+        ProjectType::Custom => {
+            info!("Custom project detected. Publishing...");
+            let upload = project.build().upload()?;
 
             let mut wasm_modules = Vec::new();
+            for file in upload.wasm_modules() {
+                wasm_modules.push(WasmModule::new(file.path, file.binding)?);
+            }
 
-            if bundle.has_wasm() {
-                let path = bundle.wasm_path();
-                let binding = bundle.get_wasm_binding();
-                let wasm_module = WasmModule::new(path, binding)?;
-                wasm_modules.push(wasm_module)
+            let mut text_blobs = Vec::new();
+            for file in upload.text_blobs() {
+                text_blobs.push(TextBlob::new(file.path, file.binding)?);
             }
 
-            let assets = ProjectAssets::new(script_path, wasm_modules, kv_namespaces)?;
+            ProjectAssets::new(upload.main, wasm_modules, text_blobs, kv_namespaces)
+        }
+    }
+}
+
+fn bundle_assets(
+    project: &Project,
+    bundle: &wranglerjs::Bundle,
+) -> Result<ProjectAssets, failure::Error> {
+    let script_path = bundle.script_path();
+
+    let mut wasm_modules = Vec::new();
 
-            build_form(&assets)
+    for (name, path) in bundle.wasm_modules()? {
+        let binding = bundle.get_wasm_binding(&name);
+        let wasm_module = WasmModule::new(path, binding)?;
+        wasm_modules.push(wasm_module)
+    }
+
+    ProjectAssets::new(
+        script_path,
+        wasm_modules,
+        Vec::new(),
+        project.kv_namespaces(),
+    )
+}
+
+// The assets of every worker the project uploads: its own, or those of each
+// webpack configuration when building all of them, by name.
+fn worker_assets(
+    project: &Project,
+) -> Result<Vec<(Option<String>, ProjectAssets)>, failure::Error> {
+    if let ProjectType::Webpack = project.project_type {
+        if let Some(named_bundles) = wranglerjs::Bundle::new().named_bundles()? {
+            let mut assets = Vec::new();
+            for (name, bundle) in named_bundles {
+                assets.push((Some(name), bundle_assets(project, &bundle)?));
+            }
+            return Ok(assets);
         }
     }
+
+    Ok(vec![(None, project_assets(project)?)])
 }
 
 fn build_form(assets: &ProjectAssets) -> Result<Form, failure::Error> {
@@ -92,6 +229,10 @@ fn add_files(mut form: Form, assets: &ProjectAssets) -> Result<Form, failure::Er
         form = form.file(wasm_module.filename(), wasm_module.path())?;
     }
 
+    for text_blob in &assets.text_blobs {
+        form = form.file(text_blob.filename(), text_blob.path())?;
+    }
+
     Ok(form)
 }
 
@@ -114,23 +255,3 @@ fn filename_from_path(path: &str) -> Option<String> {
     let path = Path::new(path);
     path.file_stem()?.to_str().map(|s| s.to_string())
 }
-
-fn build_generated_dir() -> Result<(), failure::Error> {
-    let dir = "./worker/generated";
-    if !Path::new(dir).is_dir() {
-        fs::create_dir("./worker/generated")?;
-    }
-
-    Ok(())
-}
-
-fn concat_js(name: &str) -> Result<(), failure::Error> {
-    let bindgen_js_path = format!("./pkg/{}.js", name);
-    let bindgen_js: String = fs::read_to_string(bindgen_js_path)?.parse()?;
-
-    let worker_js: String = fs::read_to_string("./worker/worker.js")?.parse()?;
-    let js = format!("{} {}", bindgen_js, worker_js);
-
-    fs::write("./worker/generated/script.js", js.as_bytes())?;
-    Ok(())
-}
@@ -1,8 +1,14 @@
+use std::fs;
+use std::io::prelude::*;
+
 use failure::format_err;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 
 use super::binding::Binding;
 use super::filename_from_path;
 use super::kv_namespace::KvNamespace;
+use super::text_blob::TextBlob;
 use super::wasm_module::WasmModule;
 
 #[derive(Debug)]
@@ -10,6 +16,7 @@ pub struct ProjectAssets {
     script_name: String,
     script_path: String,
     pub wasm_modules: Vec<WasmModule>,
+    pub text_blobs: Vec<TextBlob>,
     pub kv_namespaces: Vec<KvNamespace>,
 }
 
@@ -17,15 +24,33 @@ impl ProjectAssets {
     pub fn new(
         script_path: String,
         wasm_modules: Vec<WasmModule>,
+        text_blobs: Vec<TextBlob>,
         kv_namespaces: Vec<KvNamespace>,
     ) -> Result<Self, failure::Error> {
         let script_name = filename_from_path(&script_path)
             .ok_or_else(|| format_err!("filename should not be empty: {}", script_path))?;
 
+        // every file is a part of the upload form, named after the file
+        let mut parts = vec![script_name.clone()];
+        let filenames = wasm_modules
+            .iter()
+            .map(WasmModule::filename)
+            .chain(text_blobs.iter().map(TextBlob::filename));
+        for filename in filenames {
+            if parts.contains(&filename) {
+                failure::bail!(
+                    "Can't upload more than one file named `{}`, rename one of them",
+                    filename
+                );
+            }
+            parts.push(filename);
+        }
+
         Ok(Self {
             script_name,
             script_path,
             wasm_modules,
+            text_blobs,
             kv_namespaces,
         })
     }
@@ -37,6 +62,10 @@ impl ProjectAssets {
             let binding = wm.binding();
             bindings.push(binding);
         }
+        for tb in &self.text_blobs {
+            let binding = tb.binding();
+            bindings.push(binding);
+        }
         for kv in &self.kv_namespaces {
             let binding = kv.binding();
             bindings.push(binding);
@@ -45,6 +74,21 @@ impl ProjectAssets {
         bindings
     }
 
+    // The gzipped size of the script, wasm modules and text blobs, as uploaded.
+    pub fn compressed_size(&self) -> Result<u64, failure::Error> {
+        let mut e = GzEncoder::new(Vec::new(), Compression::default());
+
+        e.write_all(&fs::read(&self.script_path)?)?;
+        for wasm_module in &self.wasm_modules {
+            e.write_all(&fs::read(wasm_module.path())?)?;
+        }
+        for text_blob in &self.text_blobs {
+            e.write_all(&fs::read(text_blob.path())?)?;
+        }
+
+        Ok(e.finish()?.len() as u64)
+    }
+
     pub fn script_name(&self) -> String {
         self.script_name.to_string()
     }
@@ -53,3 +97,63 @@ impl ProjectAssets {
         self.script_path.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn it_measures_the_files_it_uploads() {
+        let dir = env::temp_dir().join("it_measures_the_files_it_uploads");
+        fs::create_dir_all(&dir).unwrap();
+
+        let script_path = dir.join("script.js");
+        let wasm_path = dir.join("module.wasm");
+        fs::write(&script_path, "a".repeat(10_000)).unwrap();
+        fs::write(&wasm_path, vec![0u8; 10_000]).unwrap();
+
+        let script_only = ProjectAssets::new(
+            script_path.to_str().unwrap().to_string(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        let wasm_module =
+            WasmModule::new(wasm_path.to_str().unwrap().to_string(), "wasm".to_string()).unwrap();
+        let with_wasm = ProjectAssets::new(
+            script_path.to_str().unwrap().to_string(),
+            vec![wasm_module],
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+
+        // gzipped, so much smaller than the 10 KB of each file
+        let script_size = script_only.compressed_size().unwrap();
+        let total_size = with_wasm.compressed_size().unwrap();
+        assert!(script_size < 1_000);
+        assert!(total_size > script_size && total_size < 2_000);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_refuses_two_files_with_the_same_part_name() {
+        let wasm_module =
+            WasmModule::new("./dist/worker.wasm".to_string(), "WASM".to_string()).unwrap();
+        let text_blob =
+            TextBlob::new("./dist/template.html".to_string(), "TEMPLATE".to_string()).unwrap();
+        let same_name_as_wasm =
+            TextBlob::new("./static/worker.txt".to_string(), "TEXT".to_string()).unwrap();
+
+        assert!(ProjectAssets::new(
+            "./dist/index.js".to_string(),
+            vec![wasm_module],
+            vec![text_blob, same_name_as_wasm],
+            Vec::new(),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::util::human_size;
+
+use crate::settings::project::Limits;
+use crate::terminal::{emoji, message};
+
+use super::project_assets::ProjectAssets;
+
+// The gzipped size of a worker's upload, against the limits in wrangler.toml.
+#[derive(Debug)]
+pub struct ProjectSize {
+    compressed_size: u64,
+    max_size: u64,
+    warn_size: u64,
+}
+
+impl ProjectSize {
+    pub fn new(assets: &ProjectAssets, limits: &Limits) -> Result<ProjectSize, failure::Error> {
+        Ok(ProjectSize {
+            compressed_size: assets.compressed_size()?,
+            max_size: limits.max_size(),
+            warn_size: limits.warn_size(),
+        })
+    }
+
+    pub fn is_over_limit(&self) -> bool {
+        self.compressed_size > self.max_size
+    }
+
+    pub fn human_size(&self) -> String {
+        human_size(self.compressed_size)
+    }
+
+    pub fn human_limit(&self) -> String {
+        human_size(self.max_size)
+    }
+
+    // `name` is that of the webpack configuration, when the project builds
+    // several workers.
+    pub fn print(&self, name: &Option<String>) {
+        let msg = match name {
+            Some(name) => format!("Built `{}`, its size is {}", name, self.message()),
+            None => format!("Built project size is {}", self.message()),
+        };
+
+        if self.compressed_size >= self.warn_size {
+            message::warn(&msg);
+        } else {
+            message::success(&msg);
+        }
+    }
+
+    fn message(&self) -> String {
+        if self.is_over_limit() {
+            format!(
+                "{} gzipped. {} Your built project has grown past the {} size limit and will fail to publish. {1}",
+                self.human_size(),
+                emoji::WARN,
+                self.human_limit()
+            )
+        } else if self.compressed_size >= self.warn_size {
+            format!(
+                "{} gzipped. {} Your built project is {} away from reaching the {} size limit. {1}",
+                self.human_size(),
+                emoji::WARN,
+                human_size(self.max_size - self.compressed_size),
+                self.human_limit()
+            )
+        } else {
+            format!("{} gzipped.", self.human_size())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_size(compressed_size: u64, limits: &Limits) -> ProjectSize {
+        ProjectSize {
+            compressed_size,
+            max_size: limits.max_size(),
+            warn_size: limits.warn_size(),
+        }
+    }
+
+    #[test]
+    fn it_warns_over_max_size() {
+        let size = project_size(1 << 21, &Limits::default());
+
+        assert!(size.is_over_limit());
+        assert!(size.message().contains("grown past the 1.0 MiB size limit"));
+    }
+
+    #[test]
+    fn it_warns_near_max_size() {
+        let size = project_size((1 << 20) - 4096, &Limits::default());
+
+        assert!(!size.is_over_limit());
+        assert!(size.message().contains("4.0 KiB away from reaching"));
+    }
+
+    #[test]
+    fn it_uses_the_limits_from_wrangler_toml() {
+        let limits = Limits {
+            max_size: Some(100_000),
+            warn_size: Some(50_000),
+        };
+
+        assert_eq!(project_size(40_000, &limits).message(), "39.1 KiB gzipped.");
+        assert!(project_size(60_000, &limits).message().contains("reaching"));
+        assert!(project_size(100_001, &limits).is_over_limit());
+    }
+}
@@ -0,0 +1,38 @@
+use failure::format_err;
+
+use super::binding::Binding;
+use super::filename_from_path;
+
+// A text file the worker reads through a global string, such as an HTML
+// template.
+#[derive(Debug)]
+pub struct TextBlob {
+    path: String,
+    filename: String,
+    binding: String,
+}
+
+impl TextBlob {
+    pub fn new(path: String, binding: String) -> Result<Self, failure::Error> {
+        let filename = filename_from_path(&path)
+            .ok_or_else(|| format_err!("filename should not be empty: {}", path))?;
+
+        Ok(Self {
+            filename,
+            path,
+            binding,
+        })
+    }
+
+    pub fn binding(&self) -> Binding {
+        Binding::new_text_blob(&self.binding, &self.filename)
+    }
+
+    pub fn path(&self) -> String {
+        self.path.to_string()
+    }
+
+    pub fn filename(&self) -> String {
+        self.filename.to_string()
+    }
+}
@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use failure::ResultExt;
+
+use crate::install::cache;
+use crate::settings::global_user::get_global_config_dir;
+use crate::terminal::{interactive, message};
+
+/// Remove the running wrangler, and with `purge` its configuration and the
+/// tools it downloaded too.
+pub fn self_uninstall(purge: bool, yes: bool) -> Result<(), failure::Error> {
+    let me = env::current_exe()?;
+    let mut paths = Vec::new();
+    if purge {
+        paths.push(wrangler_home()?);
+        paths.push(cache::dir()?);
+    }
+
+    if !yes {
+        let mut question = format!("Remove wrangler from `{}`", me.display());
+        for path in &paths {
+            question += &format!(" and `{}`", path.display());
+        }
+        if !interactive::is_interactive() {
+            failure::bail!("{}? Pass `--yes` to confirm without a terminal", question);
+        }
+        if !interactive::confirm(&format!("{}?", question), false)? {
+            failure::bail!("aborting uninstallation");
+        }
+    }
+
+    for path in paths {
+        if path.exists() {
+            fs::remove_dir_all(&path)
+                .with_context(|_| format!("failed to remove `{}`", path.display()))?;
+            message::info(&format!("Removed `{}`", path.display()));
+        }
+    }
+
+    if cfg!(windows) {
+        // Windows won't delete a running executable, so leave it to a shell
+        // that waits for wrangler to exit
+        Command::new("cmd")
+            .arg("/C")
+            .arg(format!("timeout /T 2 >NUL & del \"{}\"", me.display()))
+            .spawn()?;
+    } else {
+        fs::remove_file(&me).with_context(|_| format!("failed to remove `{}`", me.display()))?;
+    }
+
+    message::success("Uninstalled wrangler, goodbye!");
+    Ok(())
+}
+
+// `~/.wrangler`, or `WRANGLER_HOME`.
+fn wrangler_home() -> Result<PathBuf, failure::Error> {
+    let config_dir = get_global_config_dir()?;
+    match config_dir.parent() {
+        Some(home) => Ok(home.to_path_buf()),
+        None => Ok(config_dir),
+    }
+}
@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use failure::ResultExt;
+use serde::Deserialize;
+
+use crate::http;
+use crate::install::{self, cache, checksum};
+use crate::terminal::message;
+
+const LATEST_RELEASE: &str = "https://api.github.com/repos/cloudflare/wrangler/releases/latest";
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Replace the running wrangler with the latest release, if it is newer.
+pub fn self_update() -> Result<(), failure::Error> {
+    if install::is_offline() {
+        failure::bail!("wrangler is offline, and can't check for a new release");
+    }
+
+    let current = env!("CARGO_PKG_VERSION");
+    let latest = latest_release()?;
+    if !is_newer(&latest, current) {
+        message::success(&format!("wrangler {} is the latest release", current));
+        return Ok(());
+    }
+
+    let target = match install::prebuilt_target() {
+        Some(target) => target,
+        None => failure::bail!(
+            "wrangler isn't released for this platform, update it with `cargo install wrangler --force`"
+        ),
+    };
+    let url = release_url(&latest, target);
+
+    message::working(&format!("Updating wrangler {} to {}...", current, latest));
+    let data = match install::download(&url)? {
+        Some(data) => data,
+        None => failure::bail!(
+            "wrangler {} has no release for {}, update it with `cargo install wrangler --force`",
+            latest,
+            target
+        ),
+    };
+    let expected = checksum::expected("wrangler", &latest, &url)?;
+    checksum::verify(&data, &expected, &url)?;
+
+    // Extract next to the running executable, so that renaming it into place
+    // doesn't cross filesystems.
+    let me = env::current_exe()?;
+    let temp = me.with_file_name(".wrangler-update");
+    drop(fs::remove_dir_all(&temp));
+    fs::create_dir_all(&temp).with_context(|_| {
+        format!(
+            "failed to write to `{}`, do you have permission to change it?",
+            temp.display()
+        )
+    })?;
+
+    let files = install::extract(&data, &temp, &["wrangler"])
+        .map_err(|e| failure::format_err!("failed to extract {}: {}", url, e))?;
+    replace(&temp.join(&files[0]), &me)?;
+    drop(fs::remove_dir_all(&temp));
+
+    message::success(&format!(
+        "Updated wrangler to {} at `{}`",
+        latest,
+        me.display()
+    ));
+    Ok(())
+}
+
+fn latest_release() -> Result<String, failure::Error> {
+    let mut res = http::client().get(LATEST_RELEASE).send()?;
+    if !res.status().is_success() {
+        failure::bail!(
+            "failed to check for the latest wrangler release: {}",
+            res.status()
+        );
+    }
+
+    let release: Release = res.json()?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    cache::compare_versions(Some(&latest.to_string()), Some(&current.to_string()))
+        == Ordering::Greater
+}
+
+fn release_url(version: &str, target: &str) -> String {
+    format!(
+        "https://github.com/cloudflare/wrangler/releases/download/v{0}/wrangler-v{0}-{1}.tar.gz",
+        version, target
+    )
+}
+
+// Rename the new executable over the current one, so that wrangler is never
+// half written. Windows won't replace a running executable, but does let it
+// be renamed out of the way.
+fn replace(new: &Path, current: &Path) -> Result<(), failure::Error> {
+    if cfg!(windows) {
+        let old = old_executable(current);
+        drop(fs::remove_file(&old));
+        fs::rename(current, &old)?;
+    }
+
+    fs::rename(new, current)
+        .with_context(|_| format!("failed to replace `{}`", current.display()))?;
+    Ok(())
+}
+
+// Remove the executable an update on Windows renamed out of the way, which
+// couldn't be deleted while it was still running.
+pub fn remove_old_executable() {
+    if cfg!(windows) {
+        if let Ok(me) = env::current_exe() {
+            drop(fs::remove_file(old_executable(&me)));
+        }
+    }
+}
+
+fn old_executable(current: &Path) -> PathBuf {
+    current.with_extension("old.exe")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_updates_to_newer_releases() {
+        assert!(is_newer("1.2.0", "1.1.1"));
+        assert!(is_newer("1.10.0", "1.9.0"));
+        assert!(!is_newer("1.1.1", "1.1.1"));
+        assert!(!is_newer("1.0.0", "1.1.1"));
+    }
+
+    #[test]
+    fn it_finds_the_release_archive() {
+        assert_eq!(
+            release_url("1.2.0", "x86_64-apple-darwin"),
+            "https://github.com/cloudflare/wrangler/releases/download/v1.2.0/wrangler-v1.2.0-x86_64-apple-darwin.tar.gz"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use regex::{Captures, Regex};
+use sourcemap::{DecodedMap, Token};
+
+use crate::terminal::message;
+
+// Locations in a stack trace, as printed by V8: `file.js:line:column`.
+const LOCATION_PATTERN: &str = r"(?P<file>[^\s()@]+):(?P<line>\d+):(?P<col>\d+)";
+
+/// Map every location in a stack trace, read from `trace_path` or stdin, back
+/// to the original sources using the source map at `map_path`.
+pub fn symbolicate(map_path: &Path, trace_path: Option<&Path>) -> Result<(), failure::Error> {
+    let trace = match trace_path {
+        Some(trace_path) => fs::read_to_string(trace_path)?,
+        None => {
+            message::info("Paste the stack trace, then press Ctrl-D");
+            let mut trace = String::new();
+            io::stdin().read_to_string(&mut trace)?;
+            trace
+        }
+    };
+
+    let symbolicator = match Symbolicator::load(map_path)? {
+        Some(symbolicator) => symbolicator,
+        None => failure::bail!(
+            "No source map found at {}. Build your project with `wrangler build` first.",
+            map_path.display()
+        ),
+    };
+
+    println!("{}", symbolicator.symbolicate(&trace));
+    Ok(())
+}
+
+pub struct Symbolicator {
+    map: DecodedMap,
+}
+
+impl Symbolicator {
+    // Load the source map at `map_path`, if the last build wrote one.
+    pub fn load(map_path: &Path) -> Result<Option<Symbolicator>, failure::Error> {
+        if !map_path.exists() {
+            return Ok(None);
+        }
+
+        let map = sourcemap::decode_slice(&fs::read(map_path)?)?;
+        Ok(Some(Symbolicator { map }))
+    }
+
+    /// Rewrite the locations in `trace` that the source map knows about,
+    /// leaving everything else as is.
+    pub fn symbolicate(&self, trace: &str) -> String {
+        let location = Regex::new(LOCATION_PATTERN).unwrap();
+
+        location
+            .replace_all(trace, |caps: &Captures| {
+                let line: u32 = caps["line"].parse().unwrap_or(0);
+                let col: u32 = caps["col"].parse().unwrap_or(0);
+
+                // stack traces count from 1, source maps from 0
+                match self.lookup(line.saturating_sub(1), col.saturating_sub(1)) {
+                    Some(original) => original,
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    fn lookup(&self, line: u32, col: u32) -> Option<String> {
+        let (map, line, col) = match &self.map {
+            DecodedMap::Regular(map) => (map, line, col),
+            DecodedMap::Index(index) => {
+                // the last section starting at or before the location
+                let section = index
+                    .sections()
+                    .filter(|section| section.get_offset() <= (line, col))
+                    .last()?;
+                let (offset_line, offset_col) = section.get_offset();
+
+                // the column offset only applies to the first line of a section
+                let col = if line == offset_line {
+                    col - offset_col
+                } else {
+                    col
+                };
+
+                match section.get_sourcemap()? {
+                    DecodedMap::Regular(map) => (map, line - offset_line, col),
+                    DecodedMap::Index(_) => return None,
+                }
+            }
+        };
+
+        let token = map.lookup_token(line, col)?;
+
+        // the closest token may be on a previous line, which says nothing
+        // about this one
+        if token.get_dst_line() != line {
+            return None;
+        }
+
+        original_location(&token)
+    }
+}
+
+fn original_location(token: &Token) -> Option<String> {
+    let source = token.get_source()?.trim_start_matches("webpack:///");
+
+    Some(format!(
+        "{}:{}:{}",
+        source,
+        token.get_src_line() + 1,
+        token.get_src_col() + 1
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single section, mapping its first and second line to lines 10 and 20 of
+    // `src/index.js`, and starts 2 lines and 4 columns into the script.
+    const INDEX_MAP: &str = r#"{
+        "version": 3,
+        "sections": [{
+            "offset": { "line": 2, "column": 4 },
+            "map": {
+                "version": 3,
+                "sources": ["webpack:///src/index.js"],
+                "names": [],
+                "mappings": "AASA;AAUA"
+            }
+        }]
+    }"#;
+
+    fn symbolicator() -> Symbolicator {
+        Symbolicator {
+            map: sourcemap::decode_slice(INDEX_MAP.as_bytes()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn it_symbolicates_the_first_line_of_a_section() {
+        let trace = "Error: boom\n    at handleRequest (worker.js:3:5)";
+        assert_eq!(
+            symbolicator().symbolicate(trace),
+            "Error: boom\n    at handleRequest (src/index.js:10:1)"
+        );
+    }
+
+    #[test]
+    fn it_symbolicates_later_lines_without_the_column_offset() {
+        assert_eq!(
+            symbolicator().symbolicate("at worker.js:4:1"),
+            "at src/index.js:20:1"
+        );
+    }
+
+    #[test]
+    fn it_leaves_unknown_locations_alone() {
+        // line 1 is the prologue, which has no mapping
+        assert_eq!(
+            symbolicator().symbolicate("at worker.js:1:5"),
+            "at worker.js:1:5"
+        );
+    }
+}
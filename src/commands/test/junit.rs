@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use super::TestResult;
+
+// Render the results of a `wrangler test` run as a JUnit XML report, which
+// most CI systems know how to display.
+pub fn report(suite: &str, results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|r| !r.passed()).count();
+    let time: f64 = results.iter().map(|r| seconds(r.duration)).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += &format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        escape(suite),
+        results.len(),
+        failures,
+        time
+    );
+
+    for result in results {
+        xml += &format!(
+            "  <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&result.name),
+            escape(suite),
+            seconds(result.duration)
+        );
+
+        if result.passed() {
+            xml += "/>\n";
+        } else {
+            let message = result.failures.join("\n");
+            xml += ">\n";
+            xml += &format!(
+                "    <failure message=\"{}\">{}</failure>\n",
+                escape(&result.failures[0]),
+                escape(&message)
+            );
+            xml += "  </testcase>\n";
+        }
+    }
+
+    xml += "</testsuite>\n";
+    xml
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_millis()) / 1000.0
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reports_passes_and_failures() {
+        let results = vec![
+            TestResult {
+                name: "passes".to_string(),
+                duration: Duration::from_millis(10),
+                failures: vec![],
+            },
+            TestResult {
+                name: "<fails>".to_string(),
+                duration: Duration::from_millis(20),
+                failures: vec!["expected status 200, got 500".to_string()],
+            },
+        ];
+
+        let xml = report("worker", &results);
+
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"passes\" classname=\"worker\" time=\"0.010\"/>"));
+        assert!(xml.contains("name=\"&lt;fails&gt;\""));
+        assert!(xml.contains("<failure message=\"expected status 200, got 500\">"));
+    }
+}
@@ -0,0 +1,139 @@
+mod junit;
+mod spec;
+
+pub use spec::TestSpec;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::info;
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::commands;
+use crate::commands::publish::preview::{self, upload_and_get_id};
+use crate::http;
+use crate::settings::global_user::GlobalUser;
+use crate::settings::project::Project;
+use crate::terminal::message;
+
+use spec::TestCase;
+
+// The parts of a preview response that a {TestCase} can make assertions on.
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: u16,
+    // header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub duration: Duration,
+    pub failures: Vec<String>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+pub fn test(
+    project: &Project,
+    user: Option<&GlobalUser>,
+    spec_path: &Path,
+    junit_path: Option<&Path>,
+) -> Result<(), failure::Error> {
+    let spec = TestSpec::new(spec_path)?;
+
+    commands::build(project)?;
+    let script_id = upload_and_get_id(project, user)?;
+
+    let session = Uuid::new_v4().to_simple().to_string();
+    let cookie = preview::cookie(&script_id, &session);
+    let client = http::client();
+
+    message::working(&format!("Running {} tests...", spec.tests.len()));
+
+    let mut results = Vec::new();
+    for case in &spec.tests {
+        let start = Instant::now();
+        let failures = match send(&client, &cookie, case) {
+            Ok(response) => case.expect.check(&response),
+            Err(e) => vec![format!("request failed: {}", e)],
+        };
+
+        let result = TestResult {
+            name: case.name.clone(),
+            duration: start.elapsed(),
+            failures,
+        };
+        report(&result);
+        results.push(result);
+    }
+
+    if let Some(junit_path) = junit_path {
+        fs::write(junit_path, junit::report(&project.name, &results))?;
+        message::info(&format!("Wrote JUnit report to {}", junit_path.display()));
+    }
+
+    let failed = results.iter().filter(|r| !r.passed()).count();
+    if failed > 0 {
+        failure::bail!("{} of {} tests failed", failed, results.len())
+    }
+
+    message::success(&format!("All {} tests passed!", results.len()));
+    Ok(())
+}
+
+fn send(client: &Client, cookie: &str, case: &TestCase) -> Result<TestResponse, failure::Error> {
+    let url = format!("{}{}", preview::PREVIEW_ADDRESS, case.path);
+    info!("{} {}", case.method, url);
+
+    let mut request = client
+        .request(case.method()?, &url)
+        .header("Cookie", cookie);
+
+    for (name, value) in &case.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+
+    if let Some(body) = &case.body {
+        request = request.body(body.clone());
+    }
+
+    let mut res = request.send()?;
+
+    let headers = res
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            (name.as_str().to_lowercase(), value)
+        })
+        .collect();
+
+    Ok(TestResponse {
+        status: res.status().as_u16(),
+        headers,
+        body: res.text()?,
+    })
+}
+
+fn report(result: &TestResult) {
+    let elapsed = result.duration.as_millis();
+    if result.passed() {
+        message::success(&format!("PASS {} ({}ms)", result.name, elapsed));
+    } else {
+        let mut msg = format!("FAIL {} ({}ms)", result.name, elapsed);
+        for failure in &result.failures {
+            msg += &format!("\n    - {}", failure);
+        }
+        message::user_error(&msg);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use reqwest::Method;
+use serde::Deserialize;
+
+use super::TestResponse;
+
+// A {TestSpec} is the list of requests `wrangler test` sends to the preview
+// of a worker, along with what each response is expected to look like. It
+// can be written in TOML or JSON; the format is picked from the file
+// extension.
+#[derive(Debug, Deserialize)]
+pub struct TestSpec {
+    pub tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Expectation {
+    pub status: Option<u16>,
+    // header name => regex the header value must match
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body_contains: Vec<String>,
+    #[serde(default)]
+    pub body_matches: Vec<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_path() -> String {
+    "/".to_string()
+}
+
+impl TestSpec {
+    pub fn new(spec_path: &Path) -> Result<TestSpec, failure::Error> {
+        if !spec_path.is_file() {
+            failure::bail!(
+                "Could not find a test spec at `{}`; pass the path to your test spec file.",
+                spec_path.display()
+            )
+        }
+
+        let contents = fs::read_to_string(spec_path)?;
+        let is_json = spec_path.extension() == Some(OsStr::new("json"));
+
+        let spec = if is_json {
+            TestSpec::from_json(&contents)?
+        } else {
+            TestSpec::from_toml(&contents)?
+        };
+
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn from_toml(contents: &str) -> Result<TestSpec, failure::Error> {
+        match toml::from_str(contents) {
+            Ok(spec) => Ok(spec),
+            Err(e) => failure::bail!("Your test spec has an error: {}", e),
+        }
+    }
+
+    fn from_json(contents: &str) -> Result<TestSpec, failure::Error> {
+        match serde_json::from_str(contents) {
+            Ok(spec) => Ok(spec),
+            Err(e) => failure::bail!("Your test spec has an error: {}", e),
+        }
+    }
+
+    // Catch mistakes in the spec up front, rather than reporting them as
+    // failures of every test run against the worker.
+    fn validate(&self) -> Result<(), failure::Error> {
+        if self.tests.is_empty() {
+            failure::bail!("Your test spec does not contain any tests.")
+        }
+
+        for case in &self.tests {
+            case.method()?;
+
+            // it is appended to the preview address as is
+            if !case.path.starts_with('/') {
+                failure::bail!(
+                    "Test \"{}\" has a path that doesn't start with `/`: {}",
+                    case.name,
+                    case.path
+                )
+            }
+
+            let patterns = case
+                .expect
+                .headers
+                .values()
+                .chain(&case.expect.body_matches);
+            for pattern in patterns {
+                if let Err(e) = Regex::new(pattern) {
+                    failure::bail!("Test \"{}\" has an invalid regex: {}", case.name, e)
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TestCase {
+    pub fn method(&self) -> Result<Method, failure::Error> {
+        match Method::from_bytes(self.method.to_uppercase().as_bytes()) {
+            Ok(method) => Ok(method),
+            Err(_) => failure::bail!(
+                "Test \"{}\" has an invalid method: {}",
+                self.name,
+                self.method
+            ),
+        }
+    }
+}
+
+impl Expectation {
+    // Returns a description of every expectation the response does not meet.
+    pub fn check(&self, response: &TestResponse) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if let Some(status) = self.status {
+            if status != response.status {
+                failures.push(format!(
+                    "expected status {}, got {}",
+                    status, response.status
+                ));
+            }
+        }
+
+        for (name, pattern) in &self.headers {
+            let re = Regex::new(pattern).expect("test spec regexes are validated on load");
+            match response.headers.get(&name.to_lowercase()) {
+                Some(value) if re.is_match(value) => {}
+                Some(value) => failures.push(format!(
+                    "expected header {} to match /{}/, got {:?}",
+                    name, pattern, value
+                )),
+                None => failures.push(format!("expected header {} to be present", name)),
+            }
+        }
+
+        for needle in &self.body_contains {
+            if !response.body.contains(needle.as_str()) {
+                failures.push(format!("expected body to contain {:?}", needle));
+            }
+        }
+
+        for pattern in &self.body_matches {
+            let re = Regex::new(pattern).expect("test spec regexes are validated on load");
+            if !re.is_match(&response.body) {
+                failures.push(format!("expected body to match /{}/", pattern));
+            }
+        }
+
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> TestResponse {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "content-type".to_string(),
+            "text/html; charset=utf-8".to_string(),
+        );
+
+        TestResponse {
+            status: 200,
+            headers,
+            body: "<h1>hello world</h1>".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_parses_a_toml_spec() {
+        let spec = TestSpec::from_toml(
+            r#"
+            [[tests]]
+            name = "home page"
+
+            [tests.expect]
+            status = 200
+            body_contains = ["hello"]
+
+            [[tests]]
+            name = "post"
+            method = "post"
+            path = "/api"
+            body = "hi"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.tests.len(), 2);
+        assert_eq!(spec.tests[0].method, "GET");
+        assert_eq!(spec.tests[0].path, "/");
+        assert_eq!(spec.tests[1].method().unwrap(), Method::POST);
+        assert!(spec.validate().is_ok());
+    }
+
+    #[test]
+    fn it_parses_a_json_spec() {
+        let spec = TestSpec::from_json(
+            r#"{ "tests": [{ "name": "home", "expect": { "headers": { "Content-Type": "html" } } }] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.tests[0].expect.headers["Content-Type"], "html");
+    }
+
+    #[test]
+    fn it_rejects_invalid_regexes() {
+        let spec = TestSpec::from_toml(
+            r#"
+            [[tests]]
+            name = "broken"
+            [tests.expect]
+            body_matches = ["("]
+        "#,
+        )
+        .unwrap();
+
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn it_rejects_relative_paths() {
+        let spec = TestSpec::from_toml(
+            r#"
+            [[tests]]
+            name = "api"
+            path = "api"
+        "#,
+        )
+        .unwrap();
+
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn it_passes_matching_responses() {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "^text/html".to_string());
+        let expect = Expectation {
+            status: Some(200),
+            headers,
+            body_contains: vec!["hello".to_string()],
+            body_matches: vec!["<h1>.*</h1>".to_string()],
+        };
+
+        assert!(expect.check(&response()).is_empty());
+    }
+
+    #[test]
+    fn it_reports_each_failed_expectation() {
+        let mut headers = HashMap::new();
+        headers.insert("x-missing".to_string(), ".*".to_string());
+        let expect = Expectation {
+            status: Some(404),
+            headers,
+            body_contains: vec!["goodbye".to_string()],
+            body_matches: vec![],
+        };
+
+        assert_eq!(expect.check(&response()).len(), 3);
+    }
+}
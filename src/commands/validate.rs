@@ -0,0 +1,258 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::build::wranglerjs::Bundle;
+use crate::settings::project::{Project, ProjectType};
+use crate::terminal::message;
+
+// Check the wrangler.toml of the current directory and the files it refers
+// to, without building anything.
+pub fn validate() -> Result<(), failure::Error> {
+    // reports what is wrong with wrangler.toml itself
+    let project = Project::new()?;
+
+    let problems = problems(&project, Path::new("./"));
+    if problems.is_empty() {
+        message::success("Your wrangler.toml is valid");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        message::warn(problem);
+    }
+    failure::bail!(
+        "Found {} problem{} with your project",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    )
+}
+
+// What would make building or publishing the project in `dir` fail.
+fn problems(project: &Project, dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match project.project_type {
+        ProjectType::Webpack => match &project.webpack_config {
+            Some(webpack_config) => {
+                if !dir.join(webpack_config).is_file() {
+                    problems.push(format!(
+                        "`webpack_config` is `{}`, which doesn't exist",
+                        webpack_config
+                    ));
+                }
+            }
+            // without a webpack configuration, wranglerjs bundles the
+            // package's main
+            None => {
+                if !dir.join("webpack.config.js").is_file() {
+                    check_package_main(dir, &mut problems);
+                }
+            }
+        },
+        ProjectType::JavaScript => check_package_main(dir, &mut problems),
+        ProjectType::Rust => {
+            if !dir.join("Cargo.toml").is_file() {
+                problems.push("Rust projects need a Cargo.toml".to_string());
+            }
+        }
+        ProjectType::Custom => {
+            let build = project.build();
+            if let Err(e) = build.command() {
+                problems.push(e.to_string());
+            }
+            if let Err(e) = build.upload() {
+                problems.push(e.to_string());
+            }
+            if !dir.join(build.cwd()).is_dir() {
+                problems.push(format!(
+                    "The `cwd` of [build] is `{}`, which doesn't exist",
+                    build.cwd().display()
+                ));
+            }
+        }
+    }
+
+    check_bindings(project, dir, &mut problems);
+    problems
+}
+
+fn check_package_main(dir: &Path, problems: &mut Vec<String>) {
+    let package_json = dir.join("package.json");
+    let package: serde_json::Value = match fs::read_to_string(&package_json) {
+        Ok(package) => match serde_json::from_str(&package) {
+            Ok(package) => package,
+            Err(e) => {
+                problems.push(format!("package.json is invalid: {}", e));
+                return;
+            }
+        },
+        Err(_) => {
+            problems.push(
+                "There is no webpack.config.js, so wrangler bundles the `main` of your package.json, but there is no package.json".to_string(),
+            );
+            return;
+        }
+    };
+
+    match package.get("main").and_then(|main| main.as_str()) {
+        Some(main) if !main.is_empty() => {
+            if !dir.join(main).is_file() {
+                problems.push(format!(
+                    "The `main` of your package.json is `{}`, which doesn't exist",
+                    main
+                ));
+            }
+        }
+        _ => problems
+            .push("Your package.json needs a `main`, the entrypoint of your worker".to_string()),
+    }
+}
+
+// Every binding is a global of the worker script, so their names must differ.
+// Those of webpack's wasm modules are only known once built, so they come from
+// the last build, if any.
+fn check_bindings(project: &Project, dir: &Path, problems: &mut Vec<String>) {
+    let mut bindings: Vec<(String, &str)> = Vec::new();
+    match project.project_type {
+        ProjectType::Rust => bindings.push(("wasm".to_string(), "the wasm module of your crate")),
+        ProjectType::Webpack => {
+            for binding in webpack_wasm_bindings(dir) {
+                bindings.push((binding, "the wasm modules of your webpack build"));
+            }
+        }
+        _ => (),
+    }
+    for kv in project.kv_namespaces() {
+        bindings.push((kv.binding, "kv-namespaces"));
+    }
+    if let ProjectType::Custom = project.project_type {
+        if let Ok(upload) = project.build().upload() {
+            for file in upload.wasm_modules() {
+                bindings.push((file.binding, "build.upload.wasm_modules"));
+            }
+            for file in upload.text_blobs() {
+                bindings.push((file.binding, "build.upload.text_blobs"));
+            }
+        }
+    }
+
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    for (binding, section) in bindings {
+        if binding.is_empty() {
+            problems.push(format!("A binding of `{}` has no name", section));
+        } else if let Some(first) = seen.get(&binding) {
+            problems.push(format!(
+                "The binding `{}` of `{}` is already a binding of `{}`",
+                binding, section, first
+            ));
+        } else {
+            seen.insert(binding, section);
+        }
+    }
+}
+
+// The bindings of the wasm modules in the last webpack build of `dir`, in
+// every worker it built. A bundle that can't be read tells nothing.
+fn webpack_wasm_bindings(dir: &Path) -> BTreeSet<String> {
+    let bundle = Bundle::new_at(dir.join("worker").to_string_lossy().to_string());
+    let bundles = match bundle.named_bundles() {
+        Ok(Some(named_bundles)) => named_bundles.into_iter().map(|(_, b)| b).collect(),
+        _ => vec![bundle],
+    };
+
+    let mut bindings = BTreeSet::new();
+    for bundle in bundles {
+        for (name, _) in bundle.wasm_modules().unwrap_or_default() {
+            bindings.insert(bundle.get_wasm_binding(&name));
+        }
+    }
+    bindings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::project::KvNamespace;
+    use crate::util::temp_dir_with;
+
+    #[test]
+    fn it_checks_the_package_main() {
+        let project = Project::scaffold("worker".to_string(), ProjectType::JavaScript);
+
+        let dir = temp_dir_with(
+            "it_checks_the_package_main",
+            &[("package.json", "{\"main\": \"index.js\"}")],
+        );
+        assert_eq!(
+            problems(&project, &dir),
+            vec!["The `main` of your package.json is `index.js`, which doesn't exist"]
+        );
+
+        fs::write(dir.join("index.js"), "").unwrap();
+        assert_eq!(problems(&project, &dir), Vec::<String>::new());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_checks_the_webpack_config() {
+        let mut project = Project::scaffold("worker".to_string(), ProjectType::Webpack);
+        project.webpack_config = Some("webpack.worker.js".to_string());
+
+        let dir = temp_dir_with("it_checks_the_webpack_config", &[]);
+        assert_eq!(
+            problems(&project, &dir),
+            vec!["`webpack_config` is `webpack.worker.js`, which doesn't exist"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_checks_binding_names_are_unique() {
+        let mut project = Project::scaffold("worker".to_string(), ProjectType::Rust);
+        let kv = |binding: &str| KvNamespace {
+            id: "0f2ac74b498b48028cb68387c421e279".to_string(),
+            binding: binding.to_string(),
+        };
+        project.kv_namespaces = Some(vec![kv("CACHE"), kv("SESSIONS"), kv("CACHE")]);
+
+        let dir = temp_dir_with("it_checks_binding_names_are_unique", &[("Cargo.toml", "")]);
+        assert_eq!(
+            problems(&project, &dir),
+            vec!["The binding `CACHE` of `kv-namespaces` is already a binding of `kv-namespaces`"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_checks_bindings_against_the_wasm_modules() {
+        let kv = |binding: &str| KvNamespace {
+            id: "0f2ac74b498b48028cb68387c421e279".to_string(),
+            binding: binding.to_string(),
+        };
+
+        let mut project = Project::scaffold("worker".to_string(), ProjectType::Rust);
+        project.kv_namespaces = Some(vec![kv("wasm")]);
+        let dir = temp_dir_with(
+            "it_checks_bindings_against_the_rust_wasm",
+            &[("Cargo.toml", "")],
+        );
+        assert_eq!(
+            problems(&project, &dir),
+            vec!["The binding `wasm` of `kv-namespaces` is already a binding of `the wasm module of your crate`"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut project = Project::scaffold("worker".to_string(), ProjectType::Webpack);
+        project.kv_namespaces = Some(vec![kv("wasmprogram_foo_bg")]);
+        let dir = temp_dir_with(
+            "it_checks_bindings_against_the_webpack_wasm",
+            &[("webpack.config.js", ""), ("worker/foo_bg.wasm", "")],
+        );
+        assert_eq!(
+            problems(&project, &dir),
+            vec!["The binding `wasmprogram_foo_bg` of `kv-namespaces` is already a binding of `the wasm modules of your webpack build`"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,14 @@
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use reqwest::{Client, ClientBuilder, RedirectPolicy};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use std::time::Duration;
 
 use crate::install;
 use crate::settings::global_user::GlobalUser;
 
+pub const API: &str = "https://api.cloudflare.com/client/v4";
+
 fn headers() -> HeaderMap {
     let version = if install::target::DEBUG {
         "dev"
@@ -32,6 +36,15 @@ pub fn client() -> Client {
         .expect("could not create http client")
 }
 
+// For release assets, which take longer than an API call.
+pub fn download_client() -> Client {
+    builder()
+        .timeout(Duration::from_secs(300))
+        .default_headers(headers())
+        .build()
+        .expect("could not create http client")
+}
+
 pub fn auth_client(user: &GlobalUser) -> Client {
     let mut headers = headers();
     headers.insert("X-Auth-Key", HeaderValue::from_str(&user.api_key).unwrap());
@@ -43,3 +56,51 @@ pub fn auth_client(user: &GlobalUser) -> Client {
         .build()
         .expect("could not create authenticated http client")
 }
+
+// One page of a list from the API.
+#[derive(Deserialize)]
+struct Page<T> {
+    success: bool,
+    errors: Vec<ApiError>,
+    result: Option<Vec<T>>,
+    result_info: Option<ResultInfo>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: u32,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ResultInfo {
+    total_pages: u32,
+}
+
+// Every item of a paginated list from the API.
+pub fn list<T: DeserializeOwned>(client: &Client, url: &str) -> Result<Vec<T>, failure::Error> {
+    let mut items = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let mut res = client
+            .get(url)
+            .query(&[("page", page), ("per_page", 50)])
+            .send()?;
+        let body: Page<T> = serde_json::from_str(&res.text()?)?;
+        if !body.success {
+            let errors: Vec<String> = body
+                .errors
+                .iter()
+                .map(|e| format!("{} (code {})", e.message, e.code))
+                .collect();
+            failure::bail!("Failed to list {}: {}", url, errors.join(", "));
+        }
+
+        items.extend(body.result.unwrap_or_default());
+        match body.result_info {
+            Some(info) if page < info.total_pages => page += 1,
+            _ => return Ok(items),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::cmp::Ordering;
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use fs2::FileExt;
+use log::info;
+
+use super::checksum;
+use super::{installed_version, TOOLS};
+use crate::terminal::message;
+
+// Each download is extracted to `<tool>-<version>`, next to these files.
+// Older versions of wrangler named the directory after a hash of the URL.
+const VERSION_FILE: &str = ".version";
+// `<sha256>  <path>` for every file extracted from the download.
+const CHECKSUMS_FILE: &str = ".checksums";
+// Touched every time a build uses the entry.
+const LAST_USED_FILE: &str = ".last-used";
+
+// A tool in the cache.
+pub struct Entry {
+    pub version: Option<String>,
+    pub path: PathBuf,
+    pub last_used: Option<SystemTime>,
+}
+
+pub enum Verification {
+    Verified,
+    // the files that changed since they were downloaded
+    Mismatch(Vec<String>),
+    // downloaded before wrangler verified checksums
+    Unverifiable,
+}
+
+// Where tools are downloaded to, `WRANGLER_CACHE` if set.
+pub fn dir() -> Result<PathBuf, failure::Error> {
+    let dir = match env::var("WRANGLER_CACHE") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => dirs::cache_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| {
+                failure::format_err!("couldn't find your home directory, is $HOME not set?")
+            })?
+            .join(".wrangler"),
+    };
+
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn entry_path(root: &Path, tool_name: &str, version: &str) -> PathBuf {
+    root.join(format!("{}-{}", tool_name, version))
+}
+
+// Hold this lock while changing a cache entry, so that two wranglers don't
+// download the same tool at once.
+pub fn lock(path: &Path) -> Result<File, failure::Error> {
+    let flock = File::create(lock_path(path)?)?;
+    flock.lock_exclusive()?;
+    Ok(flock)
+}
+
+// Remove the lock file of an entry that is gone, once it is no longer held.
+// Should another wrangler have taken it since, the file stays for it.
+pub fn remove_lock(path: &Path) -> Result<(), failure::Error> {
+    let lock_path = lock_path(path)?;
+    if let Err(e) = fs::remove_file(&lock_path) {
+        info!("Couldn't remove {}: {}", lock_path.display(), e);
+    }
+    Ok(())
+}
+
+fn lock_path(path: &Path) -> Result<PathBuf, failure::Error> {
+    let dirname = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| failure::format_err!("{} is not a cache entry", path.display()))?;
+    Ok(path.with_file_name(format!(".{}.lock", dirname)))
+}
+
+pub fn record_version(path: &Path, version: &str) -> Result<(), failure::Error> {
+    fs::write(path.join(VERSION_FILE), version)?;
+    Ok(())
+}
+
+// Note that an entry was just used, for `wrangler cache clean --older-than`.
+pub fn touch(path: &Path) -> Result<(), failure::Error> {
+    fs::write(path.join(LAST_USED_FILE), "")?;
+    Ok(())
+}
+
+// When an entry was last used, or downloaded if it never was.
+pub fn last_used(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path.join(LAST_USED_FILE))
+        .or_else(|_| fs::metadata(path))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// The size of an entry on disk, in bytes.
+pub fn size(path: &Path) -> Result<u64, failure::Error> {
+    let mut size = 0;
+    for dir_entry in fs::read_dir(path)? {
+        let dir_entry = dir_entry?;
+        let metadata = dir_entry.metadata()?;
+        size += if metadata.is_dir() {
+            self::size(&dir_entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+
+    Ok(size)
+}
+
+// Record the checksums of the files of an entry, relative to it.
+pub fn record_checksums(path: &Path, files: &[PathBuf]) -> Result<(), failure::Error> {
+    let mut checksums = String::new();
+    for file in files {
+        checksums += &format!(
+            "{}  {}\n",
+            checksum::sha256_file(&path.join(file))?,
+            file.display()
+        );
+    }
+
+    fs::write(path.join(CHECKSUMS_FILE), checksums)?;
+    Ok(())
+}
+
+// Check the files of an entry against the checksums recorded when it was
+// downloaded.
+pub fn verify(path: &Path) -> Result<Verification, failure::Error> {
+    let checksums = match fs::read_to_string(path.join(CHECKSUMS_FILE)) {
+        Ok(checksums) => checksums,
+        Err(_) => return Ok(Verification::Unverifiable),
+    };
+
+    let mut mismatches = Vec::new();
+    for line in checksums.lines() {
+        let mut parts = line.splitn(2, "  ");
+        let (expected, file) = match (parts.next(), parts.next()) {
+            (Some(expected), Some(file)) => (expected, file),
+            _ => continue,
+        };
+
+        let file_path = path.join(file);
+        let matches = file_path.is_file() && checksum::sha256_file(&file_path)? == expected;
+        if !matches {
+            mismatches.push(file.to_string());
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(Verification::Verified)
+    } else {
+        Ok(Verification::Mismatch(mismatches))
+    }
+}
+
+// Every cached version of a tool.
+pub fn entries(root: &Path, tool_name: &str) -> Result<Vec<Entry>, failure::Error> {
+    let mut entries = Vec::new();
+
+    for dir_entry in fs::read_dir(root)? {
+        let path = dir_entry?.path();
+        let dirname = match path.file_name().and_then(|name| name.to_str()) {
+            Some(dirname) => dirname.to_string(),
+            None => continue,
+        };
+
+        if !path.is_dir() || tool_of(&dirname) != Some(tool_name) {
+            continue;
+        }
+
+        let version = match fs::read_to_string(path.join(VERSION_FILE)) {
+            Ok(version) => Some(version.trim().to_string()),
+            // cached by an older wrangler, ask the tool itself
+            Err(_) => installed_version(&path.join(tool_name)),
+        };
+
+        let last_used = last_used(&path);
+        entries.push(Entry {
+            version,
+            path,
+            last_used,
+        });
+    }
+
+    Ok(entries)
+}
+
+// The cached tool of the given version, or the newest one. Only verified
+// entries are used; those that changed since they were downloaded are
+// removed.
+pub fn find(
+    root: &Path,
+    tool_name: &str,
+    version: &Option<String>,
+) -> Result<Option<Entry>, failure::Error> {
+    let mut verified = Vec::new();
+    for entry in entries(root, tool_name)? {
+        match verify(&entry.path)? {
+            Verification::Verified => verified.push(entry),
+            Verification::Mismatch(_) => {
+                // another wrangler may have replaced or removed the entry
+                // meanwhile, so check it once more under the lock
+                let _lock = lock(&entry.path)?;
+                let files = match verify(&entry.path)? {
+                    Verification::Mismatch(files) => files,
+                    Verification::Verified => {
+                        verified.push(entry);
+                        continue;
+                    }
+                    Verification::Unverifiable => continue,
+                };
+
+                message::warn(&format!(
+                    "Removing {} from the cache, these files don't match their checksums: {}",
+                    entry.path.display(),
+                    files.join(", ")
+                ));
+                fs::remove_dir_all(&entry.path)?;
+            }
+            Verification::Unverifiable => {
+                info!("skipping unverified {}", entry.path.display());
+            }
+        }
+    }
+
+    let found = match version {
+        Some(version) => verified
+            .into_iter()
+            .find(|entry| entry.version.as_ref() == Some(version)),
+        None => verified
+            .into_iter()
+            .filter(|entry| entry.version.is_some())
+            .max_by(|a, b| compare_versions(a.version.as_ref(), b.version.as_ref())),
+    };
+
+    Ok(found)
+}
+
+// The tool a cache directory belongs to: `wasm-pack-0.8.1` is wasm-pack's,
+// even if a tool were named `wasm`. Dot directories are
+// in-progress downloads.
+fn tool_of(dirname: &str) -> Option<&'static str> {
+    TOOLS
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| dirname.starts_with(&format!("{}-", name)))
+        .max_by_key(|name| name.len())
+}
+
+pub fn compare_versions(a: Option<&String>, b: Option<&String>) -> Ordering {
+    let parts = |version: Option<&String>| -> Vec<u64> {
+        version
+            .map(|version| {
+                version
+                    .split('.')
+                    .map(|part| {
+                        let digits: String =
+                            part.chars().take_while(|c| c.is_ascii_digit()).collect();
+                        digits.parse().unwrap_or(0)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    parts(a).cmp(&parts(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::temp_dir_with;
+
+    fn cache_with(name: &str, entries: &[(&str, &str)]) -> PathBuf {
+        let binaries: Vec<(String, &str)> = entries
+            .iter()
+            .map(|(dirname, _)| (format!("{}/bin", dirname), *dirname))
+            .collect();
+        let files: Vec<(&str, &str)> = binaries
+            .iter()
+            .map(|(file, contents)| (file.as_str(), *contents))
+            .collect();
+        let root = temp_dir_with(name, &files);
+
+        for (dirname, version) in entries {
+            let path = root.join(dirname);
+            record_version(&path, version).unwrap();
+            record_checksums(&path, &[PathBuf::from("bin")]).unwrap();
+        }
+
+        root
+    }
+
+    #[test]
+    fn it_finds_the_newest_cached_version() {
+        let root = cache_with(
+            "it_finds_the_newest_cached_version",
+            &[
+                ("wasm-pack-0.9.1", "0.9.1"),
+                ("wasm-pack-0.10.0", "0.10.0"),
+                ("wranglerjs-1.2.0", "1.2.0"),
+            ],
+        );
+
+        let newest = find(&root, "wasm-pack", &None).unwrap().unwrap();
+        assert_eq!(newest.version, Some("0.10.0".to_string()));
+        assert!(newest.path.ends_with("wasm-pack-0.10.0"));
+
+        let pinned = find(&root, "wasm-pack", &Some("0.9.1".to_string())).unwrap();
+        assert!(pinned.unwrap().path.ends_with("wasm-pack-0.9.1"));
+
+        assert!(find(&root, "wasm-pack", &Some("0.8.0".to_string()))
+            .unwrap()
+            .is_none());
+        assert!(find(&root, "cargo", &None).unwrap().is_none());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn it_removes_entries_that_changed_since_they_were_downloaded() {
+        let root = cache_with(
+            "it_removes_entries_that_changed_since_they_were_downloaded",
+            &[("wasm-pack-0.9.1", "0.9.1")],
+        );
+        let path = root.join("wasm-pack-0.9.1");
+        fs::write(path.join("bin"), "tampered").unwrap();
+
+        match verify(&path).unwrap() {
+            Verification::Mismatch(files) => assert_eq!(files, vec!["bin"]),
+            _ => panic!("a tampered file should not verify"),
+        }
+
+        assert!(find(&root, "wasm-pack", &None).unwrap().is_none());
+        assert!(!path.exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+
+use crate::http;
+
+// The checksum of the wranglerjs release built along with this wrangler,
+// set by the release pipeline.
+const WRANGLERJS_SHA256: Option<&str> = option_env!("WRANGLERJS_SHA256");
+
+pub fn sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, failure::Error> {
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?;
+    let mut buf = [0; 64 * 1024];
+
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.input(&buf[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.result()))
+}
+
+// The checksum a release asset must have, refusing assets without one.
+pub fn expected(tool_name: &str, version: &str, url: &str) -> Result<String, failure::Error> {
+    match published(tool_name, version, url)? {
+        Some(checksum) => Ok(checksum),
+        None => failure::bail!(
+            "no checksum was published for {} at {}.sha256, refusing to install an unverified download",
+            url,
+            url
+        ),
+    }
+}
+
+// The checksum of a release asset: embedded in wrangler for the matching
+// wranglerjs, else published next to the asset as `<asset>.sha256`. Not
+// every project publishes one, wasm-pack doesn't.
+pub fn published(
+    tool_name: &str,
+    version: &str,
+    url: &str,
+) -> Result<Option<String>, failure::Error> {
+    if tool_name == "wranglerjs" && version == env!("CARGO_PKG_VERSION") {
+        if let Some(checksum) = WRANGLERJS_SHA256 {
+            return Ok(Some(checksum.to_lowercase()));
+        }
+    }
+
+    let checksum_url = format!("{}.sha256", url);
+    let mut res = http::client().get(&checksum_url).send()?;
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        failure::bail!("failed to download {}: {}", checksum_url, res.status());
+    }
+
+    match parse_checksum_file(&res.text()?) {
+        Some(checksum) => Ok(Some(checksum)),
+        None => failure::bail!("{} is not a SHA-256 checksum file", checksum_url),
+    }
+}
+
+pub fn verify(data: &[u8], expected: &str, url: &str) -> Result<(), failure::Error> {
+    let actual = sha256(data);
+    if actual != expected {
+        failure::bail!(
+            "the checksum of {} doesn't match, refusing to install it: expected {}, got {}",
+            url,
+            expected,
+            actual
+        );
+    }
+
+    Ok(())
+}
+
+// The checksum in the output of `sha256sum`, `<hex>  <file name>`, or a bare
+// checksum.
+fn parse_checksum_file(contents: &str) -> Option<String> {
+    let checksum = contents.split_whitespace().next()?.to_lowercase();
+    if checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(checksum)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_hashes_with_sha256() {
+        assert_eq!(
+            sha256(b"wrangler"),
+            "b6086e6137a8d3b5df439a842a2840850713632fef199d0aedbcc3a36e9e0ccc"
+        );
+    }
+
+    #[test]
+    fn it_reads_checksum_files() {
+        let checksum = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            parse_checksum_file(&format!("{}  wasm-pack.tar.gz\n", checksum)),
+            Some(checksum.to_lowercase())
+        );
+        assert_eq!(parse_checksum_file("Not Found"), None);
+    }
+
+    #[test]
+    fn it_refuses_a_mismatching_download() {
+        let expected = sha256(b"the release");
+        assert!(verify(b"the release", &expected, "url").is_ok());
+        assert!(verify(b"something else", &expected, "url").is_err());
+    }
+}
@@ -1,64 +1,253 @@
+pub mod cache;
+pub mod checksum;
 mod krate;
 pub mod target;
 
-use crate::terminal::emoji;
+use crate::http;
+use crate::settings::tools::Tools;
+use crate::terminal::{emoji, message};
 
-use binary_install::{Cache, Download};
+use binary_install::Download;
+use cache::Verification;
+use flate2::read::GzDecoder;
 use krate::Krate;
 use log::info;
+use reqwest::StatusCode;
 use which::which;
 
 use std::env;
-use std::path::Path;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use lazy_static::lazy_static;
+// The tools wrangler installs, and the GitHub owner of their releases.
+pub const TOOLS: &[(&str, &str)] = &[("wasm-pack", "rustwasm"), ("wranglerjs", "cloudflare")];
 
-lazy_static! {
-    static ref CACHE: Cache = get_wrangler_cache().expect("creating binary dependency cache");
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+// Never touch the network for the rest of this run, as with `--offline`.
+pub fn set_offline() {
+    OFFLINE.store(true, Ordering::SeqCst);
 }
 
-pub fn install(tool_name: &str, owner: &str) -> Result<Download, failure::Error> {
-    if let Some(download) = tool_exists(tool_name) {
-        return Ok(download);
+// Offline, tools are only used from the PATH or the cache.
+pub fn is_offline() -> bool {
+    if OFFLINE.load(Ordering::SeqCst) {
+        return true;
+    }
+
+    match env::var("WRANGLER_OFFLINE") {
+        Ok(value) => !(value.is_empty() || value == "0" || value == "false"),
+        Err(_) => false,
     }
+}
+
+// A tool ready to run: which version it is, and why that one.
+pub struct Resolved {
+    pub name: String,
+    pub download: Download,
+    // unknown when a tool on the PATH doesn't report its version
+    pub version: Option<String>,
+    pub origin: Origin,
+}
+
+pub enum Origin {
+    Path,
+    Pinned,
+    Latest,
+    Cached,
+}
+
+pub fn install(tool_name: &str, owner: &str, tools: &Tools) -> Result<Download, failure::Error> {
+    Ok(resolve(tool_name, owner, tools)?.download)
+}
+
+// Find the version of a tool to run: the one on the PATH, unless it doesn't
+// match the version pinned in `tools`, else the pinned or latest release.
+pub fn resolve(tool_name: &str, owner: &str, tools: &Tools) -> Result<Resolved, failure::Error> {
+    let pinned = tools.version(tool_name);
+
+    if let Some(resolved) = on_path(tool_name, &pinned) {
+        return Ok(resolved);
+    }
+
+    if is_offline() {
+        return cached(tool_name, &pinned);
+    }
+
+    let (version, origin) = match pinned {
+        Some(pinned) => (pinned, Origin::Pinned),
+        None => (latest_version(tool_name)?, Origin::Latest),
+    };
 
+    Ok(Resolved {
+        name: tool_name.to_string(),
+        download: fetch(tool_name, owner, &version)?,
+        version: Some(version),
+        origin,
+    })
+}
+
+// The tool on the PATH, unless it doesn't report the `pinned` version.
+fn on_path(tool_name: &str, pinned: &Option<String>) -> Option<Resolved> {
+    let path = tool_path(tool_name)?;
+    let version = installed_version(&path);
+    let origin = match pinned {
+        None => Origin::Path,
+        Some(pinned) if version.as_ref() == Some(pinned) => Origin::Pinned,
+        Some(pinned) => {
+            message::info(&format!(
+                "Found {} {} on your PATH, but version {} is pinned",
+                tool_name,
+                version
+                    .clone()
+                    .unwrap_or_else(|| "of an unknown version".to_string()),
+                pinned
+            ));
+            return None;
+        }
+    };
+
+    let no_parent_msg = format!("{} There is no path parent", emoji::WARN);
+    Some(Resolved {
+        name: tool_name.to_string(),
+        download: Download::at(path.parent().expect(&no_parent_msg)),
+        version,
+        origin,
+    })
+}
+
+// Download a release of a tool to the cache, unless it's already there.
+pub fn fetch(tool_name: &str, owner: &str, version: &str) -> Result<Download, failure::Error> {
     let binaries = &[tool_name];
-    let latest_version = get_latest_version(tool_name)?;
-    let download = download_prebuilt(tool_name, owner, &latest_version, binaries);
-    match download {
-        Ok(download) => Ok(download),
+    let download = match download_prebuilt(tool_name, owner, version, binaries) {
+        Ok(download) => download,
         Err(e) => {
-            failure::bail!("could not download pre-built `{}` ({}).", tool_name, e);
+            failure::bail!(
+                "could not download pre-built `{}` {} ({}).",
+                tool_name,
+                version,
+                e
+            );
+        }
+    };
+
+    Ok(download)
+}
+
+// The pinned version of a tool from the cache, or its newest cached one.
+fn cached(tool_name: &str, pinned: &Option<String>) -> Result<Resolved, failure::Error> {
+    match cache::find(&cache::dir()?, tool_name, pinned)? {
+        Some(entry) => {
+            cache::touch(&entry.path)?;
+            Ok(Resolved {
+                name: tool_name.to_string(),
+                download: Download::at(&entry.path),
+                version: entry.version,
+                origin: Origin::Cached,
+            })
         }
+        None => failure::bail!(not_cached_message(tool_name, pinned)),
+    }
+}
+
+fn not_cached_message(tool_name: &str, version: &Option<String>) -> String {
+    let (tool, spec) = match version {
+        Some(version) => (
+            format!("`{}` {}", tool_name, version),
+            format!("{}@{}", tool_name, version),
+        ),
+        None => (format!("`{}`", tool_name), tool_name.to_string()),
+    };
+
+    format!(
+        "{} is not in the tool cache, and wrangler is offline. Run `wrangler install-tools {}` while online to fetch it.",
+        tool, spec
+    )
+}
+
+impl fmt::Display for Resolved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let origin = match self.origin {
+            Origin::Path => "from your PATH",
+            Origin::Pinned => "pinned",
+            Origin::Latest => "latest",
+            Origin::Cached => "cached",
+        };
+
+        write!(
+            f,
+            "{} {} ({}) at {}",
+            self.name,
+            self.version
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            origin,
+            self.download.path().display()
+        )
     }
 }
 
+// Install a release artifact of `version`. Like `resolve`, a copy on the PATH
+// is only used when it is that version; otherwise the download is checked
+// against its checksums.
 pub fn install_artifact(
     tool_name: &str,
     owner: &str,
     version: &str,
 ) -> Result<Download, failure::Error> {
-    if let Some(download) = tool_exists(tool_name) {
-        return Ok(download);
+    let pinned = Some(version.to_string());
+    if let Some(resolved) = on_path(tool_name, &pinned) {
+        return Ok(resolved.download);
     }
 
-    let download = download_prebuilt(tool_name, owner, version, &[]);
-    match download {
-        Ok(download) => Ok(download),
+    if is_offline() {
+        return match cache::find(&cache::dir()?, tool_name, &pinned)? {
+            Some(entry) => Ok(Download::at(&entry.path)),
+            None => failure::bail!(not_cached_message(tool_name, &pinned)),
+        };
+    }
+
+    let download = match download_prebuilt(tool_name, owner, version, &[]) {
+        Ok(download) => download,
         Err(e) => {
             failure::bail!("could not download pre-built `{}` ({}).", tool_name, e);
         }
+    };
+
+    Ok(download)
+}
+
+fn tool_path(tool_name: &str) -> Option<PathBuf> {
+    match which(tool_name) {
+        Ok(path) => {
+            log::debug!("found global {} binary at: {}", tool_name, path.display());
+            Some(path)
+        }
+        Err(_) => None,
     }
 }
 
-fn tool_exists(tool_name: &str) -> Option<Download> {
-    if let Ok(path) = which(tool_name) {
-        log::debug!("found global {} binary at: {}", tool_name, path.display());
-        let no_parent_msg = format!("{} There is no path parent", emoji::WARN);
-        Some(Download::at(path.parent().expect(&no_parent_msg)))
-    } else {
-        None
+// The version a tool reports with `--version`.
+fn installed_version(path: &Path) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
     }
+
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+// The version in the output of `tool --version`, e.g. `0.8.1` for
+// `wasm-pack 0.8.1`.
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()) && word.contains('.'))
+        .map(String::from)
 }
 
 fn download_prebuilt(
@@ -67,30 +256,192 @@ fn download_prebuilt(
     version: &str,
     binaries: &[&str],
 ) -> Result<Download, failure::Error> {
-    let url = match prebuilt_url(tool_name, owner, version) {
-        Some(url) => url,
-        None => failure::bail!(format!(
-            "no prebuilt {} binaries are available for this platform",
-            tool_name
-        )),
-    };
+    let root = cache::dir()?;
+    let destination = cache::entry_path(&root, tool_name, version);
+    let _lock = cache::lock(&destination)?;
 
-    info!("prebuilt artifact {}", url);
+    if destination.exists() {
+        if let Verification::Verified = cache::verify(&destination)? {
+            cache::touch(&destination)?;
+            return Ok(Download::at(&destination));
+        }
 
-    // no binaries are expected; downloading it as an artifact
-    let res = if !binaries.is_empty() {
-        CACHE.download(true, tool_name, binaries, &url)?
-    } else {
-        CACHE.download_artifact(tool_name, &url)?
+        message::warn(&format!(
+            "The cached {} {} doesn't match the checksums of its download, downloading it again",
+            tool_name, version
+        ));
+        fs::remove_dir_all(&destination)?;
+    }
+
+    // Extract everything in a temporary directory in case we're ctrl-c'd,
+    // so that the cache never holds half a tool.
+    let temp = root.join(format!(".{}-{}", tool_name, version));
+    drop(fs::remove_dir_all(&temp));
+    fs::create_dir_all(&temp)?;
+
+    let prebuilt = match prebuilt_url(tool_name, owner, version) {
+        Some(url) => download(&url)?.map(|data| (url, data)),
+        None => None,
     };
 
-    match res {
-        Some(download) => {
+    let files = match prebuilt {
+        Some((url, data)) => {
+            info!("prebuilt artifact {}", url);
             println!("⬇️ Installing {}...", tool_name);
-            Ok(download)
+
+            // our own releases always have a checksum; other projects, such
+            // as wasm-pack, may publish none, and then the one recorded in
+            // the cache below catches any later change
+            let expected = if tool_name == "wranglerjs" {
+                Some(checksum::expected(tool_name, version, &url)?)
+            } else {
+                checksum::published(tool_name, version, &url)?
+            };
+            match expected {
+                Some(expected) => checksum::verify(&data, &expected, &url)?,
+                None => message::warn(&format!(
+                    "No checksum was published for {}, so it can't be verified",
+                    url
+                )),
+            }
+
+            extract(&data, &temp, binaries)
+                .map_err(|e| failure::format_err!("failed to extract {}: {}", url, e))?
+        }
+        // crates.io checks the checksums of what cargo builds from
+        None if binaries.len() == 1 && binaries[0] == tool_name => {
+            cargo_install(tool_name, version, &temp)?
+        }
+        None => failure::bail!(
+            "no prebuilt {} {} is available for this platform",
+            tool_name,
+            version
+        ),
+    };
+    cache::record_version(&temp, version)?;
+    cache::record_checksums(&temp, &files)?;
+    cache::touch(&temp)?;
+
+    fs::rename(&temp, &destination)?;
+    Ok(Download::at(&destination))
+}
+
+// The release asset at `url`, if it was published.
+pub fn download(url: &str) -> Result<Option<Vec<u8>>, failure::Error> {
+    let mut res = http::download_client().get(url).send()?;
+    if res.status() == StatusCode::NOT_FOUND {
+        info!("no release asset at {}", url);
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        failure::bail!("failed to download {}: {}", url, res.status());
+    }
+
+    let mut data = Vec::new();
+    res.copy_to(&mut data)?;
+    Ok(Some(data))
+}
+
+// Build a tool from its crate when it has no release for this platform,
+// with `cargo install` into `dst`. Returns the path of the binary in `dst`.
+fn cargo_install(
+    tool_name: &str,
+    version: &str,
+    dst: &Path,
+) -> Result<Vec<PathBuf>, failure::Error> {
+    let cargo = match which("cargo") {
+        Ok(cargo) => cargo,
+        Err(_) => failure::bail!(
+            "no prebuilt {} {} is available for this platform, and building it needs a Rust toolchain. Install one from https://rustup.rs, or put {} on your PATH.",
+            tool_name,
+            version,
+            tool_name
+        ),
+    };
+
+    message::working(&format!(
+        "No prebuilt {} {} for this platform, building it with `cargo install`...",
+        tool_name, version
+    ));
+
+    let mut command = Command::new(cargo);
+    command
+        .args(["install", tool_name, "--version", version, "--root"])
+        .arg(dst);
+    info!("Running {:?}", command);
+
+    let status = command.status()?;
+    if !status.success() {
+        failure::bail!(
+            "failed to build {} {} with `cargo install`: exited with {}",
+            tool_name,
+            version,
+            status
+        );
+    }
+
+    // cargo installs into `bin`, next to its own bookkeeping
+    let binary = PathBuf::from(tool_name).with_extension(env::consts::EXE_EXTENSION);
+    fs::rename(dst.join("bin").join(&binary), dst.join(&binary))?;
+    for leftover in &["bin", ".crates.toml", ".crates2.json"] {
+        let path = dst.join(leftover);
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else if path.exists() {
+            fs::remove_file(path)?;
         }
-        None => failure::bail!("{} is not installed!", tool_name),
     }
+
+    Ok(vec![binary])
+}
+
+// Extract a release tarball, returning the paths of the extracted files. With
+// `binaries`, only those are extracted, to the top of `dst`; otherwise the
+// whole archive is, as is.
+pub fn extract(data: &[u8], dst: &Path, binaries: &[&str]) -> Result<Vec<PathBuf>, failure::Error> {
+    let mut archive = tar::Archive::new(GzDecoder::new(data));
+    let mut missing: Vec<&str> = binaries.to_vec();
+    let mut files = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path: PathBuf = entry
+            .path()?
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+
+        if binaries.is_empty() {
+            if entry.unpack_in(dst)? {
+                files.push(path);
+            }
+            continue;
+        }
+
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        let file_name = path.file_name().map(PathBuf::from);
+        match (stem, file_name) {
+            (Some(stem), Some(file_name)) if missing.contains(&stem) => {
+                missing.retain(|binary| *binary != stem);
+                entry.unpack(dst.join(&file_name))?;
+                files.push(file_name);
+            }
+            _ => continue,
+        }
+    }
+
+    if !missing.is_empty() {
+        failure::bail!("the archive is missing {}", missing.join(", "));
+    }
+
+    Ok(files)
 }
 
 fn prebuilt_url(tool_name: &str, owner: &str, version: &str) -> Option<String> {
@@ -100,15 +451,7 @@ fn prebuilt_url(tool_name: &str, owner: &str, version: &str) -> Option<String> {
             tool_name, version
         ))
     } else {
-        let target = if target::LINUX && target::x86_64 {
-            "x86_64-unknown-linux-musl"
-        } else if target::MACOS && target::x86_64 {
-            "x86_64-apple-darwin"
-        } else if target::WINDOWS && target::x86_64 {
-            "x86_64-pc-windows-msvc"
-        } else {
-            return None;
-        };
+        let target = prebuilt_target()?;
 
         let url = format!(
             "https://github.com/{0}/{1}/releases/download/v{2}/{1}-v{2}-{3}.tar.gz",
@@ -118,14 +461,127 @@ fn prebuilt_url(tool_name: &str, owner: &str, version: &str) -> Option<String> {
     }
 }
 
-fn get_latest_version(tool_name: &str) -> Result<String, failure::Error> {
+// The target of the prebuilt binaries for this platform. On Linux, a musl
+// build of wrangler gets musl binaries where both are published.
+pub fn prebuilt_target() -> Option<&'static str> {
+    target_triple(env::consts::OS, env::consts::ARCH, target::MUSL)
+}
+
+fn target_triple(os: &str, arch: &str, musl: bool) -> Option<&'static str> {
+    let triple = match (os, arch) {
+        // only musl builds are published for x86_64 Linux
+        ("linux", "x86_64") => "x86_64-unknown-linux-musl",
+        ("linux", "aarch64") if musl => "aarch64-unknown-linux-musl",
+        ("linux", "aarch64") => "aarch64-unknown-linux-gnu",
+        ("linux", "arm") if musl => "armv7-unknown-linux-musleabihf",
+        ("linux", "arm") => "armv7-unknown-linux-gnueabihf",
+        ("macos", "x86_64") => "x86_64-apple-darwin",
+        ("windows", "x86_64") => "x86_64-pc-windows-msvc",
+        _ => return None,
+    };
+
+    Some(triple)
+}
+
+pub fn latest_version(tool_name: &str) -> Result<String, failure::Error> {
     Ok(Krate::new(tool_name)?.max_version)
 }
 
-fn get_wrangler_cache() -> Result<Cache, failure::Error> {
-    if let Ok(path) = env::var("WRANGLER_CACHE") {
-        Ok(Cache::at(Path::new(&path)))
-    } else {
-        Cache::new("wrangler")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_version_a_tool_reports() {
+        assert_eq!(
+            parse_version("wasm-pack 0.8.1\n"),
+            Some("0.8.1".to_string())
+        );
+        assert_eq!(
+            parse_version("cargo-generate v0.4.0 (abcdef 2019-06-01)"),
+            Some("0.4.0".to_string())
+        );
+        assert_eq!(parse_version("no version here"), None);
+    }
+
+    fn tarball(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn it_extracts_only_the_expected_binaries() {
+        let dst = env::temp_dir().join("it_extracts_only_the_expected_binaries");
+        drop(fs::remove_dir_all(&dst));
+        fs::create_dir_all(&dst).unwrap();
+
+        let data = tarball(&[
+            ("wasm-pack-v0.8.1/LICENSE", b"MIT"),
+            ("wasm-pack-v0.8.1/wasm-pack", b"binary"),
+        ]);
+
+        let files = extract(&data, &dst, &["wasm-pack"]).unwrap();
+        assert_eq!(files, vec![PathBuf::from("wasm-pack")]);
+        assert!(dst.join("wasm-pack").is_file());
+        assert!(!dst.join("LICENSE").exists());
+
+        assert!(extract(&data, &dst, &["cargo-generate"]).is_err());
+
+        fs::remove_dir_all(dst).unwrap();
+    }
+
+    #[test]
+    fn it_extracts_artifacts_as_they_are() {
+        let dst = env::temp_dir().join("it_extracts_artifacts_as_they_are");
+        drop(fs::remove_dir_all(&dst));
+        fs::create_dir_all(&dst).unwrap();
+
+        let data = tarball(&[("./index.js", b"js"), ("lib/util.js", b"util")]);
+
+        let files = extract(&data, &dst, &[]).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("index.js"), PathBuf::from("lib/util.js")]
+        );
+        assert!(dst.join("lib/util.js").is_file());
+
+        fs::remove_dir_all(dst).unwrap();
+    }
+
+    #[test]
+    fn it_picks_the_prebuilt_target_of_the_platform() {
+        assert_eq!(
+            target_triple("linux", "x86_64", false),
+            Some("x86_64-unknown-linux-musl")
+        );
+        assert_eq!(
+            target_triple("linux", "aarch64", false),
+            Some("aarch64-unknown-linux-gnu")
+        );
+        assert_eq!(
+            target_triple("linux", "aarch64", true),
+            Some("aarch64-unknown-linux-musl")
+        );
+        assert_eq!(
+            target_triple("linux", "arm", false),
+            Some("armv7-unknown-linux-gnueabihf")
+        );
+        assert_eq!(target_triple("freebsd", "x86_64", false), None);
+    }
+
+    #[test]
+    fn it_tells_how_to_fetch_a_tool_missing_offline() {
+        assert!(not_cached_message("wasm-pack", &Some("0.8.1".to_string()))
+            .contains("wrangler install-tools wasm-pack@0.8.1"));
+        assert!(not_cached_message("wasm-pack", &None)
+            .ends_with("Run `wrangler install-tools wasm-pack` while online to fetch it."));
     }
 }
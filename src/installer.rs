@@ -3,7 +3,8 @@
 //! This module contains one public function which will self-install the
 //! currently running executable as `wrangler`. Our goal is to install this in
 //! a place that's already in `PATH`, ideally in an idiomatic location. To that
-//! end we place `wrangler` next to the `rustup` executable in `PATH`.
+//! end we place `wrangler` next to the `rustup` executable in `PATH`, or in
+//! `<prefix>/bin` when given `--prefix <prefix>` on machines without rustup.
 //!
 //! This installer is run directly (probably by clicking on it) on Windows,
 //! meaning it will pop up a console (as we're a console app). Output goes to
@@ -12,14 +13,14 @@
 //! downloaded via curl/sh, and then the shell script downloads this executable
 //! and runs it.
 //!
-//! This may get more complicated over time (self upates anyone?) but for now
-//! it's pretty simple! We're largely just moving over our currently running
-//! executable to a different path.
+//! Once installed, `wrangler self-update` and `wrangler self-uninstall` take
+//! it from there. Installing is pretty simple! We're largely just moving over
+//! our currently running executable to a different path.
 
 use std::env;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use atty;
@@ -48,20 +49,14 @@ pub fn install() -> ! {
 }
 
 fn do_install() -> Result<(), failure::Error> {
-    // Find `rustup.exe` in PATH, we'll be using its installation directory as
-    // our installation directory.
-    let rustup = match which::which("rustup") {
-        Ok(path) => path,
-        Err(_) => {
-            bail!(
-                "failed to find an installation of `rustup` in `PATH`, \
-                 is rustup already installed?"
-            );
+    let installation_dir = match prefix() {
+        Some(prefix) => {
+            let bin = prefix.join("bin");
+            fs::create_dir_all(&bin)
+                .with_context(|_| format!("failed to create `{}`", bin.display()))?;
+            bin
         }
-    };
-    let installation_dir = match rustup.parent() {
-        Some(parent) => parent,
-        None => bail!("can't install when `rustup` is at the root of the filesystem"),
+        None => rustup_dir()?,
     };
     let destination = installation_dir
         .join("wrangler")
@@ -80,11 +75,56 @@ fn do_install() -> Result<(), failure::Error> {
         destination.display()
     );
 
+    let in_path = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).any(|dir| dir == installation_dir))
+        .unwrap_or(false);
+    if !in_path {
+        println!(
+            "info: add `{}` to your `PATH` to run wrangler",
+            installation_dir.display()
+        );
+    }
+
     // ... and that's it!
 
     Ok(())
 }
 
+// The directory given with `--prefix <dir>` or `--prefix=<dir>`, if any.
+fn prefix() -> Option<PathBuf> {
+    let mut args = env::args();
+    while let Some(arg) = args.next() {
+        if arg == "--prefix" {
+            return args.next().map(PathBuf::from);
+        }
+        let mut parts = arg.splitn(2, '=');
+        if let (Some("--prefix"), Some(prefix)) = (parts.next(), parts.next()) {
+            return Some(PathBuf::from(prefix));
+        }
+    }
+
+    None
+}
+
+fn rustup_dir() -> Result<PathBuf, failure::Error> {
+    // Find `rustup.exe` in PATH, we'll be using its installation directory as
+    // our installation directory.
+    let rustup = match which::which("rustup") {
+        Ok(path) => path,
+        Err(_) => {
+            bail!(
+                "failed to find an installation of `rustup` in `PATH`, \
+                 is rustup already installed? Pass `--prefix <dir>` to \
+                 install wrangler in `<dir>/bin` instead"
+            );
+        }
+    };
+    match rustup.parent() {
+        Some(parent) => Ok(parent.to_path_buf()),
+        None => bail!("can't install when `rustup` is at the root of the filesystem"),
+    }
+}
+
 fn confirm_can_overwrite(dst: &Path) -> Result<(), failure::Error> {
     // If the `-f` argument was passed, we can always overwrite everything.
     if env::args().any(|arg| arg == "-f") {
@@ -4,6 +4,7 @@
 extern crate text_io;
 
 use std::env;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use clap::{App, AppSettings, Arg, SubCommand};
@@ -37,6 +38,7 @@ fn main() -> Result<(), ExitFailure> {
             installer::install();
         }
     }
+    commands::self_update::remove_old_executable();
     Ok(run()?)
 }
 
@@ -59,7 +61,7 @@ fn run() -> Result<(), failure::Error> {
                 )
                 .arg(
                     Arg::with_name("template")
-                        .help("a link to a github template! defaults to cloudflare/worker-template")
+                        .help("a template name from `--list`, git repository, local directory or tarball to generate from! defaults to the `worker` template")
                         .index(2),
                 )
                 .arg(
@@ -68,6 +70,54 @@ fn run() -> Result<(), failure::Error> {
                         .long("type")
                         .takes_value(true)
                         .help("the type of project you want generated"),
+                )
+                .arg(
+                    Arg::with_name("branch")
+                        .long("branch")
+                        .takes_value(true)
+                        .help("the branch of a git template to generate from"),
+                )
+                .arg(
+                    Arg::with_name("subdir")
+                        .long("subdir")
+                        .takes_value(true)
+                        .help("the directory of the template, in repositories holding several"),
+                )
+                .arg(
+                    Arg::with_name("list")
+                        .long("list")
+                        .takes_value(false)
+                        .help("list the templates you can generate by name"),
+                )
+                .arg(
+                    Arg::with_name("define")
+                        .short("d")
+                        .long("define")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("key=value")
+                        .help("a value for a placeholder of the template, such as account_id=<id>"),
+                )
+                .arg(
+                    Arg::with_name("no-setup")
+                        .long("no-setup")
+                        .takes_value(false)
+                        .help("don't run the setup of the template, such as git init or npm install"),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .short("y")
+                        .long("yes")
+                        .takes_value(false)
+                        .help("run the setup commands of the template without asking"),
+                )
+                .arg(
+                    Arg::with_name("index")
+                        .long("index")
+                        .takes_value(true)
+                        .env("WRANGLER_TEMPLATE_INDEX")
+                        .help("a URL or path to a template index to use instead of the bundled one"),
                 ),
         )
         .subcommand(
@@ -86,7 +136,7 @@ fn run() -> Result<(), failure::Error> {
                         .short("t")
                         .long("type")
                         .takes_value(true)
-                        .help("the type of project you want generated"),
+                        .help("the type of your project, detected from its files when left out"),
                 ),
         )
         .subcommand(
@@ -95,6 +145,113 @@ fn run() -> Result<(), failure::Error> {
                     "{} Build your worker",
                     emoji::CRAB
                 ))
+                .arg(
+                    Arg::with_name("watch")
+                        .help("watch your project for changes and rebuild it automagically")
+                        .long("watch")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("analyze")
+                        .help("report the largest modules and packages in your webpack build")
+                        .long("analyze")
+                        .takes_value(false)
+                        .conflicts_with("watch"),
+                )
+                .arg(
+                    Arg::with_name("report")
+                        .help("also write the analysis to this path, as HTML or JSON depending on its extension")
+                        .long("report")
+                        .takes_value(true)
+                        .requires("analyze"),
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .help("print the versions of the tools your build runs")
+                        .long("verbose")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("offline")
+                        .help("build without the network, with tools from your PATH or the tool cache")
+                        .long("offline")
+                        .takes_value(false),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("cache")
+                .about(&*format!(
+                    "{} Manage the tools wrangler downloaded",
+                    emoji::DOWN
+                ))
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("list the cached tools with their size and when they were last used"),
+                )
+                .subcommand(
+                    SubCommand::with_name("clean")
+                        .about("remove cached tools, all of them unless told which to keep")
+                        .arg(
+                            Arg::with_name("older-than")
+                                .help("only remove tools no build used for this long, e.g. 30d, 2w or 12h")
+                                .long("older-than")
+                                .takes_value(true)
+                                .value_name("age"),
+                        )
+                        .arg(
+                            Arg::with_name("keep-latest")
+                                .help("keep this many of the newest versions of each tool")
+                                .long("keep-latest")
+                                .takes_value(true)
+                                .value_name("count"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("path")
+                        .about("print where tools are downloaded to"),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("check every cached tool against the checksums of its download"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("install-tools")
+                .about(&*format!(
+                    "{} Download the tools wrangler builds with, to build offline later",
+                    emoji::DOWN
+                ))
+                .arg(
+                    Arg::with_name("tools")
+                        .help("tools to download, as <tool> or <tool>@<version>; defaults to all of them")
+                        .multiple(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("self-update")
+                .about(&*format!(
+                    "{} Update wrangler to its latest release",
+                    emoji::UP
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("self-uninstall")
+                .about(&*format!("{} Uninstall wrangler", emoji::WAVING))
+                .arg(
+                    Arg::with_name("purge")
+                        .help("also remove ~/.wrangler: your configuration and the tools wrangler downloaded")
+                        .long("purge")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .help("don't ask for confirmation")
+                        .short("y")
+                        .long("yes")
+                        .takes_value(false),
+                ),
         )
         .subcommand(
             SubCommand::with_name("preview")
@@ -118,6 +275,74 @@ fn run() -> Result<(), failure::Error> {
                         .long("watch")
                         .takes_value(false),
                 )
+                .arg(
+                    Arg::with_name("port")
+                        .help("port for the --watch websocket server; overrides `livereload.port`")
+                        .long("port")
+                        .takes_value(true)
+                        .requires("watch"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .help("address to bind the --watch websocket server to; overrides `livereload.address`")
+                        .long("address")
+                        .takes_value(true)
+                        .requires("watch"),
+                )
+                .arg(
+                    Arg::with_name("allow-origin")
+                        .help("an extra origin trusted to connect to the --watch websocket server")
+                        .long("allow-origin")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("watch"),
+                )
+                .arg(
+                    Arg::with_name("allow-address")
+                        .help("an extra address trusted to connect to the --watch websocket server")
+                        .long("allow-address")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("watch"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("test")
+                .about(&*format!(
+                    "{} Run the requests in a test spec against a preview of your worker",
+                    emoji::TEST_TUBE
+                ))
+                .arg(
+                    Arg::with_name("spec")
+                        .help("path to your test spec (TOML or JSON)! defaults to 'wrangler-tests.toml'")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("junit")
+                        .help("write a JUnit XML report of the results to this path")
+                        .long("junit")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("symbolicate")
+                .about(&*format!(
+                    "{} Map a stack trace from your built worker back to your source files",
+                    emoji::MAP
+                ))
+                .arg(
+                    Arg::with_name("trace")
+                        .help("file containing the stack trace; read from stdin if omitted")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("map")
+                        .help("source map to use! defaults to the one written by `wrangler build`")
+                        .long("map")
+                        .takes_value(true),
+                ),
         )
         .subcommand(
             SubCommand::with_name("publish").about(&*format!(
@@ -129,7 +354,13 @@ fn run() -> Result<(), failure::Error> {
                     .long("release")
                     .takes_value(false)
                     .help("should this be published to a workers.dev subdomain or a domain name you have registered"),
-             ),
+             )
+            .arg(
+                Arg::with_name("ignore-size-limit")
+                    .long("ignore-size-limit")
+                    .takes_value(false)
+                    .help("publish even if your built project is over the size limit"),
+            ),
         )
         .subcommand(
             SubCommand::with_name("config")
@@ -151,6 +382,24 @@ fn run() -> Result<(), failure::Error> {
                         .required(true),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("migrate")
+                .about(&*format!(
+                    "{} Update your wrangler.toml to the current format",
+                    emoji::UP
+                ))
+                .arg(
+                    Arg::with_name("yes")
+                        .help("don't ask for confirmation")
+                        .short("y")
+                        .long("yes")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(SubCommand::with_name("validate").about(&*format!(
+            "{} Check your wrangler.toml and the files it refers to, without building",
+            emoji::CLIPBOARD
+        )))
         .subcommand(SubCommand::with_name("whoami").about(&*format!(
             "{} Retrieve your user info and test your auth config",
             emoji::SLEUTH
@@ -167,26 +416,34 @@ fn run() -> Result<(), failure::Error> {
 
         commands::global_config(email, api_key)?;
     } else if let Some(matches) = matches.subcommand_matches("generate") {
+        if matches.is_present("list") {
+            commands::generate::list_templates(matches.value_of("index"))?;
+            return Ok(());
+        }
+
         let name = matches.value_of("name").unwrap_or("worker");
         let project_type = match matches.value_of("type") {
             Some(s) => Some(ProjectType::from_str(&s.to_lowercase())?),
             None => None,
         };
-
-        let default_template = "https://github.com/cloudflare/worker-template";
-        let template = matches.value_of("template").unwrap_or(match project_type {
-            Some(ref pt) => match pt {
-                ProjectType::Rust => "https://github.com/cloudflare/rustwasm-worker-template",
-                _ => default_template,
-            },
-            _ => default_template,
-        });
+        let template = matches.value_of("template");
 
         info!(
-            "Generate command called with template {}, and name {}",
+            "Generate command called with template {:?}, and name {}",
             template, name
         );
-        commands::generate(name, template, project_type)?;
+        let options = commands::generate::GenerateOptions {
+            branch: matches.value_of("branch"),
+            subdir: matches.value_of("subdir"),
+            index: matches.value_of("index"),
+            defines: match matches.values_of("define") {
+                Some(defines) => defines.collect(),
+                None => Vec::new(),
+            },
+            no_setup: matches.is_present("no-setup"),
+            yes: matches.is_present("yes"),
+        };
+        commands::generate(name, template, project_type, &options)?;
     } else if let Some(matches) = matches.subcommand_matches("init") {
         let name = matches.value_of("name");
         let project_type = match matches.value_of("type") {
@@ -194,14 +451,49 @@ fn run() -> Result<(), failure::Error> {
             None => None,
         };
         commands::init(name, project_type)?;
-    } else if matches.subcommand_matches("build").is_some() {
+    } else if let Some(matches) = matches.subcommand_matches("build") {
         info!("Getting project settings");
         let project = settings::project::Project::new()?;
 
-        commands::build(&project)?;
+        if matches.is_present("offline") {
+            install::set_offline();
+        }
+
+        if matches.is_present("verbose") {
+            commands::print_tool_versions(&project)?;
+        }
+
+        if matches.is_present("watch") {
+            commands::build_and_watch(&project)?;
+        } else if matches.is_present("analyze") {
+            let report = matches.value_of("report").map(Path::new);
+            commands::build_and_analyze(&project, report)?;
+        } else {
+            commands::build(&project)?;
+            commands::publish::print_built_size(&project)?;
+        }
     } else if let Some(matches) = matches.subcommand_matches("preview") {
         info!("Getting project settings");
-        let project = settings::project::Project::new()?;
+        let mut project = settings::project::Project::new()?;
+
+        let mut livereload = project.livereload();
+        if let Some(port) = matches.value_of("port") {
+            livereload.port = Some(port.parse()?);
+        }
+        if let Some(address) = matches.value_of("address") {
+            livereload.address = Some(address.to_string());
+        }
+        if let Some(origins) = matches.values_of("allow-origin") {
+            let mut allowed_origins = livereload.allowed_origins();
+            allowed_origins.extend(origins.map(|o| o.to_string()));
+            livereload.allowed_origins = Some(allowed_origins);
+        }
+        if let Some(addresses) = matches.values_of("allow-address") {
+            let mut allowed_addresses = livereload.allowed_addresses();
+            allowed_addresses.extend(addresses.map(|a| a.to_string()));
+            livereload.allowed_addresses = Some(allowed_addresses);
+        }
+        project.livereload = Some(livereload);
 
         // the preview command can be called with or without a Global User having been config'd
         // so we convert this Result into an Option
@@ -217,6 +509,60 @@ fn run() -> Result<(), failure::Error> {
         let watch = matches.is_present("watch");
 
         commands::preview(project, user, method, body, watch)?;
+    } else if let Some(matches) = matches.subcommand_matches("test") {
+        info!("Getting project settings");
+        let project = settings::project::Project::new()?;
+
+        // like preview, tests can run with or without a Global User having been config'd
+        let user = settings::global_user::GlobalUser::new().ok();
+
+        let spec = Path::new(matches.value_of("spec").unwrap_or("wrangler-tests.toml"));
+        let junit = matches.value_of("junit").map(Path::new);
+
+        commands::test(&project, user.as_ref(), spec, junit)?;
+    } else if let Some(matches) = matches.subcommand_matches("migrate") {
+        commands::migrate(matches.is_present("yes"))?;
+    } else if matches.subcommand_matches("validate").is_some() {
+        commands::validate()?;
+    } else if let Some(matches) = matches.subcommand_matches("cache") {
+        match matches.subcommand() {
+            ("list", Some(_)) => commands::cache::list()?,
+            ("clean", Some(matches)) => {
+                let keep_latest = match matches.value_of("keep-latest") {
+                    Some(count) => Some(count.parse().map_err(|_| {
+                        failure::format_err!(
+                            "--keep-latest takes a number of versions, not `{}`",
+                            count
+                        )
+                    })?),
+                    None => None,
+                };
+
+                commands::cache::clean(matches.value_of("older-than"), keep_latest)?;
+            }
+            ("path", Some(_)) => commands::cache::path()?,
+            ("verify", Some(_)) => commands::cache::verify()?,
+            _ => unreachable!(),
+        }
+    } else if let Some(matches) = matches.subcommand_matches("install-tools") {
+        let tools: Vec<&str> = match matches.values_of("tools") {
+            Some(tools) => tools.collect(),
+            None => Vec::new(),
+        };
+
+        commands::install_tools(&tools)?;
+    } else if matches.subcommand_matches("self-update").is_some() {
+        commands::self_update()?;
+    } else if let Some(matches) = matches.subcommand_matches("self-uninstall") {
+        commands::self_uninstall(matches.is_present("purge"), matches.is_present("yes"))?;
+    } else if let Some(matches) = matches.subcommand_matches("symbolicate") {
+        let map = match matches.value_of("map") {
+            Some(map) => PathBuf::from(map),
+            None => PathBuf::from(commands::build::wranglerjs::Bundle::new().source_map_path()),
+        };
+        let trace = matches.value_of("trace").map(Path::new);
+
+        commands::symbolicate(&map, trace)?;
     } else if matches.subcommand_matches("whoami").is_some() {
         info!("Getting User settings");
         let user = settings::global_user::GlobalUser::new()?;
@@ -235,7 +581,9 @@ fn run() -> Result<(), failure::Error> {
             _ => false,
         };
 
-        commands::publish(&user, &project, release)?;
+        let ignore_size_limit = matches.is_present("ignore-size-limit");
+
+        commands::publish(&user, &project, release, ignore_size_limit)?;
     } else if let Some(matches) = matches.subcommand_matches("subdomain") {
         info!("Getting project settings");
         let project = settings::project::Project::new()?;
@@ -2,3 +2,4 @@ pub mod binding;
 pub mod global_user;
 pub mod metadata;
 pub mod project;
+pub mod tools;
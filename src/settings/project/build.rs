@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+// The `[build]` section of wrangler.toml. `command`, `cwd`, `watch_dir` and
+// `upload` describe how a custom project is built and what it uploads.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Build {
+    pub command: Option<String>,
+    // relative to the project root, like every other path in this section
+    pub cwd: Option<String>,
+    pub watch_dir: Option<String>,
+    pub upload: Option<Upload>,
+    pub rust: Option<RustBuild>,
+}
+
+// The files a custom build writes, and uploads.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Upload {
+    pub main: String,
+    pub wasm_modules: Option<Vec<UploadFile>>,
+    pub text_blobs: Option<Vec<UploadFile>>,
+}
+
+// A file bound to a global of the worker script.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UploadFile {
+    pub binding: String,
+    pub path: String,
+}
+
+impl Build {
+    // The `[build]` section `wrangler init` writes for a custom project.
+    pub fn custom() -> Build {
+        Build {
+            command: Some("npm run build".to_string()),
+            upload: Some(Upload {
+                main: "dist/worker.js".to_string(),
+                ..Upload::default()
+            }),
+            ..Build::default()
+        }
+    }
+
+    pub fn command(&self) -> Result<String, failure::Error> {
+        match &self.command {
+            Some(command) if !command.trim().is_empty() => Ok(command.to_string()),
+            _ => failure::bail!(
+                "Custom projects are built with the `command` of the [build] section in your wrangler.toml, e.g.\n\n[build]\ncommand = \"npm run build\""
+            ),
+        }
+    }
+
+    pub fn cwd(&self) -> PathBuf {
+        PathBuf::from(self.cwd.clone().unwrap_or_else(|| ".".to_string()))
+    }
+
+    pub fn watch_dir(&self) -> PathBuf {
+        PathBuf::from(self.watch_dir.clone().unwrap_or_else(|| "src".to_string()))
+    }
+
+    pub fn upload(&self) -> Result<Upload, failure::Error> {
+        match &self.upload {
+            Some(upload) if !upload.main.is_empty() => Ok(upload.clone()),
+            _ => failure::bail!(
+                "Add the script your build command writes to the [build.upload] section of your wrangler.toml, e.g.\n\n[build.upload]\nmain = \"dist/worker.js\""
+            ),
+        }
+    }
+
+    pub fn rust(&self) -> RustBuild {
+        self.rust.clone().unwrap_or_default()
+    }
+}
+
+impl Upload {
+    pub fn wasm_modules(&self) -> Vec<UploadFile> {
+        self.wasm_modules.clone().unwrap_or_default()
+    }
+
+    pub fn text_blobs(&self) -> Vec<UploadFile> {
+        self.text_blobs.clone().unwrap_or_default()
+    }
+
+    // Every file the build is expected to write.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(&self.main)];
+        for file in self.wasm_modules().iter().chain(self.text_blobs().iter()) {
+            paths.push(PathBuf::from(&file.path));
+        }
+        paths
+    }
+}
+
+// How `wasm-pack` builds a Rust worker. Unset fields keep wasm-pack's
+// defaults: a release build of the default features into `pkg`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RustBuild {
+    pub profile: Option<RustProfile>,
+    pub features: Option<Vec<String>>,
+    #[serde(alias = "no-default-features")]
+    pub no_default_features: Option<bool>,
+    #[serde(alias = "out-dir")]
+    pub out_dir: Option<String>,
+    // passed to `wasm-pack build` as is
+    #[serde(alias = "extra-args")]
+    pub extra_args: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RustProfile {
+    Dev,
+    Release,
+    Profiling,
+}
+
+impl RustBuild {
+    pub fn out_dir(&self) -> String {
+        self.out_dir.clone().unwrap_or_else(|| "pkg".to_string())
+    }
+
+    // The arguments for `wasm-pack`. Cargo flags go after `--`.
+    pub fn wasm_pack_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec!["build", "--target", "no-modules"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        if let Some(profile) = &self.profile {
+            let flag = match profile {
+                RustProfile::Dev => "--dev",
+                RustProfile::Release => "--release",
+                RustProfile::Profiling => "--profiling",
+            };
+            args.push(flag.to_string());
+        }
+
+        if let Some(out_dir) = &self.out_dir {
+            args.push("--out-dir".to_string());
+            args.push(out_dir.to_string());
+        }
+
+        args.extend(self.extra_args.clone().unwrap_or_default());
+
+        let mut cargo_args = Vec::new();
+        if let Some(features) = &self.features {
+            if !features.is_empty() {
+                cargo_args.push("--features".to_string());
+                cargo_args.push(features.join(","));
+            }
+        }
+        if self.no_default_features == Some(true) {
+            cargo_args.push("--no-default-features".to_string());
+        }
+
+        if !cargo_args.is_empty() {
+            args.push("--".to_string());
+            args.extend(cargo_args);
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_with_wasm_pack_defaults() {
+        assert_eq!(
+            RustBuild::default().wasm_pack_args(),
+            vec!["build", "--target", "no-modules"]
+        );
+        assert_eq!(RustBuild::default().out_dir(), "pkg");
+    }
+
+    #[test]
+    fn it_passes_cargo_flags_after_wasm_pack_flags() {
+        let rust_build = RustBuild {
+            profile: Some(RustProfile::Dev),
+            features: Some(vec![
+                "console_error_panic_hook".to_string(),
+                "wee_alloc".to_string(),
+            ]),
+            no_default_features: Some(true),
+            out_dir: Some("build/wasm".to_string()),
+            extra_args: Some(vec!["--no-typescript".to_string()]),
+        };
+
+        assert_eq!(
+            rust_build.wasm_pack_args(),
+            vec![
+                "build",
+                "--target",
+                "no-modules",
+                "--dev",
+                "--out-dir",
+                "build/wasm",
+                "--no-typescript",
+                "--",
+                "--features",
+                "console_error_panic_hook,wee_alloc",
+                "--no-default-features",
+            ]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+// The most a worker can weigh once gzipped, 1 MiB.
+pub const MAX_SIZE: u64 = 1 << 20;
+// Warn when less than 80 KiB are left to grow, ~92% usage
+const WARN_MARGIN: u64 = 81_920;
+
+// Size limits for the upload, gzipped, in bytes. `publish` refuses to upload
+// a project over `max_size`; builds warn from `warn_size` on.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Limits {
+    pub max_size: Option<u64>,
+    pub warn_size: Option<u64>,
+}
+
+impl Limits {
+    pub fn max_size(&self) -> u64 {
+        self.max_size.unwrap_or(MAX_SIZE)
+    }
+
+    pub fn warn_size(&self) -> u64 {
+        self.warn_size
+            .unwrap_or_else(|| self.max_size().saturating_sub(WARN_MARGIN))
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+// Settings for the websocket server `wrangler preview --watch` opens for the
+// preview page. Unset fields keep the strict defaults: a random port on
+// 127.0.0.1 that only accepts connections from cloudflareworkers.com.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct LiveReload {
+    pub port: Option<u16>,
+    pub address: Option<String>,
+    pub allowed_origins: Option<Vec<String>>,
+    pub allowed_addresses: Option<Vec<String>>,
+}
+
+impl LiveReload {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(0)
+    }
+
+    //explicitly use 127.0.0.1, since localhost can resolve to 2 addresses
+    pub fn address(&self) -> String {
+        self.address
+            .clone()
+            .unwrap_or_else(|| "127.0.0.1".to_string())
+    }
+
+    pub fn allowed_origins(&self) -> Vec<String> {
+        self.allowed_origins.clone().unwrap_or_default()
+    }
+
+    pub fn allowed_addresses(&self) -> Vec<String> {
+        self.allowed_addresses.clone().unwrap_or_default()
+    }
+}
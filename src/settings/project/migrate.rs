@@ -0,0 +1,211 @@
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+// A change of the wrangler.toml schema, and how to rewrite a file from
+// before it. Steps run in order, so add new ones at the end.
+pub struct Migration {
+    // the first version of wrangler with the new schema
+    pub version: &'static str,
+    pub description: &'static str,
+    // whether a file still needs the step
+    applies: fn(&DocumentMut) -> bool,
+    // rewrites the file, returning what is left to do by hand
+    apply: fn(&mut DocumentMut, &Context) -> Vec<String>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: "1.1.0",
+    description: "the kv-namespaces format has been stabilized",
+    applies: has_kv_namespace_names,
+    apply: kv_namespaces_as_tables,
+}];
+
+// What steps may need from outside of the file.
+pub struct Context<'a> {
+    // the id of the account's namespace with the given title
+    pub kv_namespace_id: &'a dyn Fn(&str) -> Option<String>,
+}
+
+// A wrangler.toml rewritten to the current schema.
+pub struct Migrated {
+    pub toml: String,
+    pub applied: Vec<&'static Migration>,
+    pub notes: Vec<String>,
+}
+
+// The steps a wrangler.toml needs, none if it can't be parsed.
+pub fn pending(source: &str) -> Vec<&'static Migration> {
+    match source.parse::<DocumentMut>() {
+        Ok(document) => MIGRATIONS
+            .iter()
+            .filter(|migration| (migration.applies)(&document))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Rewrite a wrangler.toml to the current schema, keeping its comments and
+// formatting.
+pub fn migrate(source: &str, context: &Context) -> Result<Migrated, failure::Error> {
+    let mut document = source
+        .parse::<DocumentMut>()
+        .map_err(|e| failure::format_err!("Your wrangler.toml is invalid: {}", e))?;
+
+    let mut applied = Vec::new();
+    let mut notes = Vec::new();
+    for migration in MIGRATIONS {
+        if (migration.applies)(&document) {
+            notes.extend((migration.apply)(&mut document, context));
+            applied.push(migration);
+        }
+    }
+
+    Ok(Migrated {
+        toml: document.to_string(),
+        applied,
+        notes,
+    })
+}
+
+// Before 1.1.0, `kv-namespaces` listed the names of namespaces, which were
+// also their bindings.
+fn has_kv_namespace_names(document: &DocumentMut) -> bool {
+    match document.get("kv-namespaces").and_then(Item::as_array) {
+        Some(namespaces) => namespaces.iter().any(|namespace| namespace.is_str()),
+        None => false,
+    }
+}
+
+fn kv_namespaces_as_tables(document: &mut DocumentMut, context: &Context) -> Vec<String> {
+    let mut notes = Vec::new();
+    let root = document.as_table_mut();
+    let (key, item) = match root.remove_entry("kv-namespaces") {
+        Some(entry) => entry,
+        None => return notes,
+    };
+
+    let mut tables = ArrayOfTables::new();
+    for namespace in item.as_array().into_iter().flat_map(|array| array.iter()) {
+        let table = match (namespace.as_str(), namespace.as_inline_table()) {
+            (Some(name), _) => {
+                let mut table = Table::new();
+                table["binding"] = value(name);
+                table["id"] = match (context.kv_namespace_id)(name) {
+                    Some(id) => value(id),
+                    None => {
+                        notes.push(format!(
+                            "Fill in the id of the `{}` namespace in your wrangler.toml",
+                            name
+                        ));
+                        value("")
+                    }
+                };
+                table
+            }
+            // a namespace already in the new format
+            (None, Some(inline)) => inline.clone().into_table(),
+            (None, None) => continue,
+        };
+        tables.push(table);
+    }
+
+    // the comments above the old key go above the first new table, and the
+    // one after its value after the table's header
+    let comment = item
+        .as_value()
+        .and_then(|value| value.decor().suffix())
+        .and_then(|suffix| suffix.as_str())
+        .unwrap_or("")
+        .to_string();
+    let comments = key
+        .leaf_decor()
+        .prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or("")
+        .trim_start_matches('\n')
+        .to_string();
+    if let Some(first) = tables.get_mut(0) {
+        first.decor_mut().set_prefix(format!("\n{}", comments));
+        first.decor_mut().set_suffix(comment);
+    }
+    root.insert("kv-namespaces", Item::ArrayOfTables(tables));
+
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(title: &str) -> Option<String> {
+        match title {
+            "prodKV" => Some("0f2ac74b498b48028cb68387c421e279".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn it_migrates_kv_namespace_names_to_tables() {
+        let source = r#"# my worker
+name = "worker"   # the script name
+type = "webpack"
+# where the data lives
+kv-namespaces = ["prodKV", "stagingKV"]  # bound by name
+account_id = ""
+
+[limits]
+max_size = 524288
+"#;
+        assert_eq!(pending(source).len(), 1);
+
+        let migrated = migrate(
+            source,
+            &Context {
+                kv_namespace_id: &ids,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            migrated.toml,
+            r#"# my worker
+name = "worker"   # the script name
+type = "webpack"
+account_id = ""
+
+[limits]
+max_size = 524288
+
+# where the data lives
+[[kv-namespaces]]  # bound by name
+binding = "prodKV"
+id = "0f2ac74b498b48028cb68387c421e279"
+
+[[kv-namespaces]]
+binding = "stagingKV"
+id = ""
+"#
+        );
+        assert_eq!(migrated.applied[0].version, "1.1.0");
+        assert_eq!(
+            migrated.notes,
+            vec!["Fill in the id of the `stagingKV` namespace in your wrangler.toml"]
+        );
+        assert!(pending(&migrated.toml).is_empty());
+    }
+
+    #[test]
+    fn it_leaves_current_files_alone() {
+        let source = "name = \"worker\"\n\n[[kv-namespaces]]\nbinding = \"KV\"\nid = \"\"\n";
+        assert!(pending(source).is_empty());
+
+        let migrated = migrate(
+            source,
+            &Context {
+                kv_namespace_id: &ids,
+            },
+        )
+        .unwrap();
+        assert_eq!(migrated.toml, source);
+        assert!(migrated.applied.is_empty());
+    }
+}
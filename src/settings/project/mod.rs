@@ -1,15 +1,27 @@
+mod build;
 pub mod kv_namespace;
+mod limits;
+mod livereload;
+pub mod migrate;
 mod project_type;
+pub mod schema;
+mod watch;
 
+pub use build::Build;
 pub use kv_namespace::KvNamespace;
+pub use limits::Limits;
+pub use livereload::LiveReload;
 pub use project_type::ProjectType;
+pub use schema::{Diagnostic, Severity};
+pub use watch::Watch;
 
+use crate::settings::tools::Tools;
 use crate::terminal::emoji;
 use crate::terminal::message;
 
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use log::info;
 
@@ -24,21 +36,24 @@ pub struct Project {
     pub zone_id: Option<String>,
     pub private: Option<bool>,
     pub webpack_config: Option<String>,
+    pub webpack_config_select: Option<String>,
     pub account_id: String,
     pub route: Option<String>,
     pub routes: Option<HashMap<String, String>>,
     #[serde(rename = "kv-namespaces")]
     pub kv_namespaces: Option<Vec<KvNamespace>>,
+    pub livereload: Option<LiveReload>,
+    pub watch: Option<Watch>,
+    pub limits: Option<Limits>,
+    pub build: Option<Build>,
+    pub tools: Option<Tools>,
 }
 
 impl Project {
-    pub fn generate(
-        name: String,
-        project_type: ProjectType,
-        init: bool,
-    ) -> Result<Project, failure::Error> {
-        let project = Project {
-            name: name.clone(),
+    // The wrangler.toml of a new project, with ids and routes to fill in.
+    pub fn scaffold(name: String, project_type: ProjectType) -> Project {
+        Project {
+            name,
             project_type: project_type.clone(),
             private: Some(false),
             zone_id: Some(String::new()),
@@ -47,19 +62,26 @@ impl Project {
             routes: None,
             kv_namespaces: None,
             webpack_config: None,
-        };
+            webpack_config_select: None,
+            livereload: None,
+            watch: None,
+            limits: None,
+            build: match project_type {
+                ProjectType::Custom => Some(Build::custom()),
+                _ => None,
+            },
+            tools: None,
+        }
+    }
 
-        let toml = toml::to_string(&project)?;
-        let config_path = if init {
-            PathBuf::from("./")
-        } else {
-            Path::new("./").join(&name)
-        };
-        let config_file = config_path.join("wrangler.toml");
+    // Write the project as the wrangler.toml of `dir`.
+    pub fn write(&self, dir: &Path) -> Result<(), failure::Error> {
+        let toml = toml::to_string(self)?;
+        let config_file = dir.join("wrangler.toml");
 
         info!("Writing a wrangler.toml file at {}", config_file.display());
         fs::write(&config_file, &toml)?;
-        Ok(project)
+        Ok(())
     }
 
     pub fn new() -> Result<Self, failure::Error> {
@@ -71,6 +93,22 @@ impl Project {
     pub fn kv_namespaces(&self) -> Vec<KvNamespace> {
         self.kv_namespaces.clone().unwrap_or_else(Vec::new)
     }
+
+    pub fn livereload(&self) -> LiveReload {
+        self.livereload.clone().unwrap_or_default()
+    }
+
+    pub fn watch(&self) -> Watch {
+        self.watch.clone().unwrap_or_default()
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits.clone().unwrap_or_default()
+    }
+
+    pub fn build(&self) -> Build {
+        self.build.clone().unwrap_or_default()
+    }
 }
 
 fn get_project_config(config_path: &Path) -> Result<Project, failure::Error> {
@@ -84,32 +122,30 @@ fn get_project_config(config_path: &Path) -> Result<Project, failure::Error> {
     // Eg.. `CF_ACCOUNT_AUTH_KEY=farts` would set the `account_auth_key` key
     s.merge(Environment::with_prefix("CF"))?;
 
-    // check for pre 1.1.0 KV namespace format
-    let kv_namespaces: Result<Vec<config::Value>, config::ConfigError> = s.get("kv-namespaces");
-
-    if let Ok(values) = kv_namespaces {
-        let old_format = values.iter().any(|val| val.clone().into_str().is_ok());
-
-        if old_format {
-            message::warn("As of 1.1.0 the kv-namespaces format has been stabilized");
-            message::info("Please add a section like this in your `wrangler.toml` for each KV Namespace you wish to bind:");
-
-            let fmt_demo = r##"
-[[kv-namespaces]]
-binding = "BINDING_NAME"
-id = "0f2ac74b498b48028cb68387c421e279"
-
-# binding is the variable name you wish to bind the namespace to in your script.
-# id is the namespace_id assigned to your kv namespace upon creation. e.g. (per namespace)
-"##;
-
-            println!("{}", fmt_demo);
-
-            let msg = format!("{0} Your project config has an error {0}", emoji::WARN);
-            failure::bail!(msg)
+    // like `File::with_name`, a path without an extension is a .toml file
+    let config_path = match config_path.extension() {
+        Some(_) => config_path.to_path_buf(),
+        None => config_path.with_extension("toml"),
+    };
+    let source = fs::read_to_string(&config_path)?;
+
+    let pending = migrate::pending(&source);
+    if !pending.is_empty() {
+        for migration in pending {
+            message::warn(&format!(
+                "As of {} {}",
+                migration.version, migration.description
+            ));
         }
+        let msg = format!(
+            "{} Your wrangler.toml is in an older format, run `wrangler migrate` to update it",
+            emoji::WARN
+        );
+        failure::bail!(msg)
     }
 
+    report(&config_path, &schema::check(&source))?;
+
     let project: Result<Project, config::ConfigError> = s.try_into();
     match project {
         Ok(s) => Ok(s),
@@ -125,5 +161,31 @@ id = "0f2ac74b498b48028cb68387c421e279"
     }
 }
 
+// Warn about what wrangler ignores in a wrangler.toml, and fail on what it
+// can't make sense of.
+fn report(config_path: &Path, diagnostics: &[Diagnostic]) -> Result<(), failure::Error> {
+    let file = config_path
+        .strip_prefix("./")
+        .unwrap_or(config_path)
+        .display();
+
+    let mut errors = Vec::new();
+    for diagnostic in diagnostics {
+        match diagnostic.severity {
+            Severity::Warning => message::warn(&format!("{}:{}", file, diagnostic)),
+            Severity::Error => errors.push(format!("{}:{}", file, diagnostic)),
+        }
+    }
+
+    if !errors.is_empty() {
+        failure::bail!(
+            "{} Your project config has an error, check your `wrangler.toml`:\n{}",
+            emoji::WARN,
+            errors.join("\n")
+        )
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests;
@@ -9,6 +9,7 @@ pub enum ProjectType {
     JavaScript,
     Rust,
     Webpack,
+    Custom,
 }
 
 impl Default for ProjectType {
@@ -23,6 +24,7 @@ impl fmt::Display for ProjectType {
             ProjectType::JavaScript => "js",
             ProjectType::Rust => "rust",
             ProjectType::Webpack => "webpack",
+            ProjectType::Custom => "custom",
         };
         write!(f, "{}", printable)
     }
@@ -36,6 +38,7 @@ impl FromStr for ProjectType {
             "javascript" => Ok(ProjectType::JavaScript),
             "rust" => Ok(ProjectType::Rust),
             "webpack" => Ok(ProjectType::Webpack),
+            "custom" => Ok(ProjectType::Custom),
             _ => failure::bail!("{} is not a valid wrangler project type!", s),
         }
     }
@@ -0,0 +1,514 @@
+use std::fmt;
+use std::ops::Range;
+
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+// What may go in a wrangler.toml, checked before the file is deserialized so
+// that typos and wrong types are pointed out where they are. Keep it in sync
+// with `Project` and the structs of its sections.
+const PROJECT: &[Key] = &[
+    Key::new("name", Type::String),
+    Key::new(
+        "type",
+        Type::OneOf(&["webpack", "javascript", "rust", "custom"]),
+    ),
+    Key::new("zone_id", Type::String),
+    Key::new("private", Type::Bool),
+    Key::new("webpack_config", Type::String),
+    Key::new("webpack_config_select", Type::String),
+    Key::new("account_id", Type::String),
+    Key::new("route", Type::String),
+    Key::new("routes", Type::Map(&Type::String)),
+    Key::new("kv-namespaces", Type::Tables(KV_NAMESPACE)),
+    Key::new("livereload", Type::Table(LIVERELOAD)),
+    Key::new("watch", Type::Table(WATCH)),
+    Key::new("limits", Type::Table(LIMITS)),
+    Key::new("build", Type::Table(BUILD)),
+    Key::new("tools", Type::Table(TOOLS)),
+];
+
+const KV_NAMESPACE: &[Key] = &[
+    Key::new("id", Type::String),
+    Key::new("binding", Type::String),
+];
+
+const LIVERELOAD: &[Key] = &[
+    Key::new("port", Type::Integer),
+    Key::new("address", Type::String),
+    Key::new("allowed_origins", Type::Strings),
+    Key::new("allowed_addresses", Type::Strings),
+];
+
+const WATCH: &[Key] = &[
+    Key::new("include", Type::Strings),
+    Key::new("exclude", Type::Strings),
+    Key::new("cooldown", Type::Integer),
+];
+
+const LIMITS: &[Key] = &[
+    Key::new("max_size", Type::Integer),
+    Key::new("warn_size", Type::Integer),
+];
+
+const BUILD: &[Key] = &[
+    Key::new("command", Type::String),
+    Key::new("cwd", Type::String),
+    Key::new("watch_dir", Type::String),
+    Key::new("upload", Type::Table(UPLOAD)),
+    Key::new("rust", Type::Table(RUST_BUILD)),
+];
+
+const UPLOAD: &[Key] = &[
+    Key::new("main", Type::String),
+    Key::new("wasm_modules", Type::Tables(UPLOAD_FILE)),
+    Key::new("text_blobs", Type::Tables(UPLOAD_FILE)),
+];
+
+const UPLOAD_FILE: &[Key] = &[
+    Key::new("binding", Type::String),
+    Key::new("path", Type::String),
+];
+
+const RUST_BUILD: &[Key] = &[
+    Key::new("profile", Type::OneOf(&["dev", "release", "profiling"])),
+    Key::new("features", Type::Strings),
+    Key::new("no_default_features", Type::Bool).alias("no-default-features"),
+    Key::new("out_dir", Type::String).alias("out-dir"),
+    Key::new("extra_args", Type::Strings).alias("extra-args"),
+];
+
+const TOOLS: &[Key] = &[
+    Key::new("wasm-pack", Type::String).alias("wasm_pack"),
+    Key::new("wranglerjs", Type::String),
+];
+
+struct Key {
+    name: &'static str,
+    // other spellings serde accepts
+    alias: Option<&'static str>,
+    value: Type,
+}
+
+impl Key {
+    const fn new(name: &'static str, value: Type) -> Key {
+        Key {
+            name,
+            alias: None,
+            value,
+        }
+    }
+
+    const fn alias(self, alias: &'static str) -> Key {
+        Key {
+            alias: Some(alias),
+            ..self
+        }
+    }
+
+    fn is(&self, name: &str) -> bool {
+        self.name == name || self.alias == Some(name)
+    }
+}
+
+enum Type {
+    String,
+    Bool,
+    Integer,
+    // one of these strings, in any case
+    OneOf(&'static [&'static str]),
+    // an array of strings
+    Strings,
+    Table(&'static [Key]),
+    // `[[tables]]`, or an array of inline tables
+    Tables(&'static [Key]),
+    // a table of any keys, with values of the given type
+    Map(&'static Type),
+}
+
+impl Type {
+    fn describe(&self) -> &'static str {
+        match self {
+            Type::String | Type::OneOf(_) => "a string",
+            Type::Bool => "a boolean",
+            Type::Integer => "an integer",
+            Type::Strings => "an array of strings",
+            Type::Table(_) | Type::Map(_) => "a table",
+            Type::Tables(_) => "an array of tables",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    // wrangler ignores what is wrong, and goes on
+    Warning,
+    Error,
+}
+
+// A problem found in a wrangler.toml, at a 1-based line and column.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+// Check the contents of a wrangler.toml against the schema.
+pub fn check(source: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        source,
+        diagnostics: Vec::new(),
+    };
+
+    match ImDocument::parse(source) {
+        Ok(document) => checker.table(document.as_table(), PROJECT, ""),
+        Err(e) => checker.push(
+            Severity::Error,
+            e.span(),
+            e.message().trim_end().to_string(),
+        ),
+    }
+
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn table(&mut self, table: &dyn TableLike, keys: &[Key], path: &str) {
+        for (name, item) in table.iter() {
+            let key_span = table.get_key_value(name).and_then(|(key, _)| key.span());
+            let full_name = join(path, name);
+
+            match keys.iter().find(|key| key.is(name)) {
+                Some(key) => self.item(item, &key.value, &full_name),
+                None => {
+                    let names: Vec<&str> = keys.iter().map(|key| key.name).collect();
+                    let message = match suggest(name, &names) {
+                        Some(suggestion) => format!(
+                            "unknown key `{}`, did you mean `{}`?",
+                            full_name,
+                            join(path, suggestion)
+                        ),
+                        None => format!("unknown key `{}` is ignored", full_name),
+                    };
+                    self.push(Severity::Warning, key_span.or_else(|| item.span()), message);
+                }
+            }
+        }
+    }
+
+    fn item(&mut self, item: &Item, expected: &Type, name: &str) {
+        match expected {
+            Type::String => self.expect(item, expected, name, item.is_str()),
+            Type::Bool => self.expect(item, expected, name, item.is_bool()),
+            Type::Integer => self.expect(item, expected, name, item.is_integer()),
+            Type::OneOf(values) => match item.as_str() {
+                Some(value) => {
+                    if !values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+                        let hint = match suggest(&value.to_lowercase(), values) {
+                            Some(suggestion) => format!(", did you mean \"{}\"?", suggestion),
+                            None => String::new(),
+                        };
+                        self.push(
+                            Severity::Error,
+                            item.span(),
+                            format!(
+                                "`{}` should be one of {}, not \"{}\"{}",
+                                name,
+                                list(values),
+                                value,
+                                hint
+                            ),
+                        );
+                    }
+                }
+                None => self.expect(item, expected, name, false),
+            },
+            Type::Strings => match item.as_array() {
+                Some(array) => {
+                    for value in array.iter() {
+                        if !value.is_str() {
+                            self.push(
+                                severity(value, &Type::String),
+                                value.span(),
+                                format!(
+                                    "`{}` should only hold strings, not {}",
+                                    name,
+                                    describe_value(value)
+                                ),
+                            );
+                        }
+                    }
+                }
+                None => self.expect(item, expected, name, false),
+            },
+            Type::Table(keys) => match item.as_table_like() {
+                Some(table) => self.table(table, keys, name),
+                None => self.expect(item, expected, name, false),
+            },
+            Type::Tables(keys) => match item {
+                Item::ArrayOfTables(tables) => {
+                    for table in tables.iter() {
+                        self.table(table, keys, name);
+                    }
+                }
+                Item::Value(Value::Array(array)) => {
+                    for value in array.iter() {
+                        match value.as_inline_table() {
+                            Some(table) => self.table(table, keys, name),
+                            None => self.push(
+                                Severity::Error,
+                                value.span(),
+                                format!(
+                                    "`{}` should only hold tables, not {}",
+                                    name,
+                                    describe_value(value)
+                                ),
+                            ),
+                        }
+                    }
+                }
+                _ => self.expect(item, expected, name, false),
+            },
+            Type::Map(value_type) => match item.as_table_like() {
+                Some(table) => {
+                    for (key, value) in table.iter() {
+                        self.item(value, value_type, &join(name, key));
+                    }
+                }
+                None => self.expect(item, expected, name, false),
+            },
+        }
+    }
+
+    fn expect(&mut self, item: &Item, expected: &Type, name: &str, ok: bool) {
+        if !ok {
+            let severity = match item.as_value() {
+                Some(value) => severity(value, expected),
+                None => Severity::Error,
+            };
+            self.push(
+                severity,
+                item.span(),
+                format!(
+                    "`{}` should be {}, not {}",
+                    name,
+                    expected.describe(),
+                    describe(item)
+                ),
+            );
+        }
+    }
+
+    fn push(&mut self, severity: Severity, span: Option<Range<usize>>, message: String) {
+        let (line, column) = match span {
+            Some(span) => position(self.source, span.start),
+            None => (1, 1),
+        };
+        self.diagnostics.push(Diagnostic {
+            severity,
+            line,
+            column,
+            message,
+        });
+    }
+}
+
+// How bad a value of the wrong type is. The config crate converts scalars
+// where it can, so `private = "true"` or `port = "8080"` still work and only
+// deserve a warning.
+fn severity(value: &Value, expected: &Type) -> Severity {
+    if converts(value, expected) {
+        Severity::Warning
+    } else {
+        Severity::Error
+    }
+}
+
+// Whether config reads `value` as `expected`, after its `into_str`,
+// `into_bool` and `into_int`.
+fn converts(value: &Value, expected: &Type) -> bool {
+    match (expected, value) {
+        (Type::String, Value::Integer(_))
+        | (Type::String, Value::Float(_))
+        | (Type::String, Value::Boolean(_))
+        | (Type::String, Value::Datetime(_)) => true,
+        (Type::Bool, Value::Integer(_)) | (Type::Bool, Value::Float(_)) => true,
+        (Type::Bool, Value::String(s)) => {
+            let s = s.value().to_lowercase();
+            ["1", "true", "on", "yes", "0", "false", "off", "no"].contains(&s.as_str())
+        }
+        (Type::Integer, Value::Boolean(_)) | (Type::Integer, Value::Float(_)) => true,
+        (Type::Integer, Value::String(s)) => {
+            let s = s.value().to_lowercase();
+            ["true", "on", "yes", "false", "off", "no"].contains(&s.as_str())
+                || s.parse::<i64>().is_ok()
+        }
+        _ => false,
+    }
+}
+
+// The line and column of a byte offset.
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+// The known name closest to a misspelled one, if any is close enough. Dashes
+// and underscores are alike, so `kv_namespaces` is `kv-namespaces`.
+fn suggest<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+    let normalized = name.to_lowercase().replace('_', "-");
+    known
+        .iter()
+        .map(|k| (strsim::levenshtein(&normalized, &k.replace('_', "-")), *k))
+        .filter(|(distance, k)| *distance <= (k.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k)
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn list(values: &[&str]) -> String {
+    let quoted: Vec<String> = values.iter().map(|v| format!("\"{}\"", v)).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => quoted.join(""),
+    }
+}
+
+fn describe(item: &Item) -> &'static str {
+    match item {
+        Item::None => "nothing",
+        Item::Value(value) => describe_value(value),
+        Item::Table(_) => "a table",
+        Item::ArrayOfTables(_) => "an array of tables",
+    }
+}
+
+fn describe_value(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "a string",
+        Value::Integer(_) => "an integer",
+        Value::Float(_) => "a float",
+        Value::Boolean(_) => "a boolean",
+        Value::Datetime(_) => "a date",
+        Value::Array(_) => "an array",
+        Value::InlineTable(_) => "a table",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        check(source).iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn it_accepts_a_valid_config() {
+        let source = r#"
+name = "worker"
+type = "Webpack"
+account_id = ""
+routes = { "example.com/*" = "production" }
+
+[[kv-namespaces]]
+binding = "KV"
+id = "0f2ac74b498b48028cb68387c421e279"
+
+[build.rust]
+out-dir = "build"
+features = ["wee_alloc"]
+
+[tools]
+wasm-pack = "0.8.1"
+"#;
+        assert_eq!(messages(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn it_suggests_known_keys_for_unknown_ones() {
+        let source = "name = \"worker\"\nzoneid = \"\"\n\n[[kv_namespaces]]\nbinding = \"KV\"\n\n[build]\ncomand = \"make\"\ncolour = true\n";
+        let diagnostics = check(source);
+
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "2:1: unknown key `zoneid`, did you mean `zone_id`?",
+                "4:3: unknown key `kv_namespaces`, did you mean `kv-namespaces`?",
+                "8:1: unknown key `build.comand`, did you mean `build.command`?",
+                "9:1: unknown key `build.colour` is ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_type_errors_where_they_are() {
+        let source = "name = \"worker\"\nprivate = \"maybe\"\ntype = \"wbpack\"\n\n[watch]\ninclude = [\"src\", []]\n\n[[kv-namespaces]]\nbinding = \"KV\"\nid = [42]\n";
+        let diagnostics = check(source);
+
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+        assert_eq!(
+            diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>(),
+            vec![
+                "2:11: `private` should be a boolean, not a string",
+                "3:8: `type` should be one of \"webpack\", \"javascript\", \"rust\" or \"custom\", not \"wbpack\", did you mean \"webpack\"?",
+                "6:19: `watch.include` should only hold strings, not an array",
+                "10:6: `kv-namespaces.id` should be a string, not an array",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_warns_about_values_config_converts() {
+        let source = "name = \"worker\"\nprivate = \"yes\"\n\n[livereload]\nport = \"8080\"\n\n[watch]\ninclude = [\"src\", 3]\ncooldown = \"500\"\n\n[[kv-namespaces]]\nbinding = \"KV\"\nid = 42\n";
+        let diagnostics = check(source);
+
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "2:11: `private` should be a boolean, not a string",
+                "5:8: `livereload.port` should be an integer, not a string",
+                "8:19: `watch.include` should only hold strings, not an integer",
+                "9:12: `watch.cooldown` should be an integer, not a string",
+                "13:6: `kv-namespaces.id` should be a string, not an integer",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_syntax_errors() {
+        let diagnostics = check("name = \"worker\"\nprivate = \n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, 2);
+    }
+}
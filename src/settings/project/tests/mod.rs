@@ -1,3 +1,4 @@
+use super::build::{RustProfile, UploadFile};
 use super::*;
 
 use std::env;
@@ -37,6 +38,118 @@ fn it_builds_from_config_with_kv() {
     }
 }
 
+#[test]
+fn it_builds_from_config_with_livereload() {
+    let toml_path = toml_fixture_path("livereload");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let livereload = project.livereload();
+
+    assert_eq!(livereload.port(), 8025);
+    assert_eq!(livereload.address(), "0.0.0.0");
+    assert_eq!(
+        livereload.allowed_origins(),
+        vec!["http://localhost:8080".to_string()]
+    );
+    assert_eq!(
+        livereload.allowed_addresses(),
+        vec!["172.17.0.1".to_string()]
+    );
+}
+
+#[test]
+fn it_defaults_livereload_to_localhost() {
+    let toml_path = toml_fixture_path("default");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let livereload = project.livereload();
+
+    assert_eq!(livereload.port(), 0);
+    assert_eq!(livereload.address(), "127.0.0.1");
+    assert!(livereload.allowed_origins().is_empty());
+    assert!(livereload.allowed_addresses().is_empty());
+}
+
+#[test]
+fn it_builds_from_config_with_watch() {
+    let toml_path = toml_fixture_path("watch");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let watch = project.watch();
+
+    assert_eq!(watch.include(), vec!["src/**".to_string()]);
+    assert_eq!(watch.exclude(), vec!["*.test.js".to_string()]);
+    assert_eq!(watch.cooldown, Some(500));
+}
+
+#[test]
+fn it_builds_from_config_with_limits() {
+    let toml_path = toml_fixture_path("limits");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let limits = project.limits();
+
+    assert_eq!(limits.max_size(), 524_288);
+    assert_eq!(limits.warn_size(), 400_000);
+}
+
+#[test]
+fn it_defaults_the_warning_to_80_kib_below_the_limit() {
+    let toml_path = toml_fixture_path("default");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let limits = project.limits();
+
+    assert_eq!(limits.max_size(), 1 << 20);
+    assert_eq!(limits.warn_size(), (1 << 20) - 81_920);
+}
+
+#[test]
+fn it_builds_from_config_with_rust_build() {
+    let toml_path = toml_fixture_path("build_rust");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let rust_build = project.build().rust();
+
+    assert_eq!(rust_build.profile, Some(RustProfile::Dev));
+    assert_eq!(rust_build.features, Some(vec!["wee_alloc".to_string()]));
+    assert_eq!(rust_build.no_default_features, Some(true));
+    assert_eq!(rust_build.out_dir(), "build/wasm");
+}
+
+#[test]
+fn it_builds_from_config_with_custom_build() {
+    let toml_path = toml_fixture_path("build_custom");
+
+    let project = get_project_config(&toml_path).unwrap();
+    let build = project.build();
+    let upload = build.upload().unwrap();
+
+    assert_eq!(build.command().unwrap(), "make worker");
+    assert_eq!(build.cwd(), PathBuf::from("worker"));
+    assert_eq!(build.watch_dir(), PathBuf::from("worker/src"));
+    assert_eq!(upload.main, "dist/worker.js");
+    assert_eq!(
+        upload.wasm_modules(),
+        vec![UploadFile {
+            binding: "WASM".to_string(),
+            path: "dist/worker.wasm".to_string(),
+        }]
+    );
+    assert_eq!(upload.text_blobs()[0].binding, "TEMPLATE");
+    assert_eq!(upload.paths().len(), 3);
+}
+
+#[test]
+fn it_needs_a_command_and_a_script_to_build_a_custom_project() {
+    let toml_path = toml_fixture_path("default");
+
+    let project = get_project_config(&toml_path).unwrap();
+
+    assert!(project.build().command().is_err());
+    assert!(project.build().upload().is_err());
+}
+
 fn toml_fixture_path(fixture: &str) -> PathBuf {
     let current_dir = env::current_dir().unwrap();
 
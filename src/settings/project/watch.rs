@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+// Settings for `--watch`. `include` globs are relative to the project root;
+// `exclude` patterns use the same syntax as a `.gitignore` file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Watch {
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    // milliseconds to wait for further changes before rebuilding
+    pub cooldown: Option<u64>,
+}
+
+impl Watch {
+    pub fn include(&self) -> Vec<String> {
+        self.include.clone().unwrap_or_default()
+    }
+
+    pub fn exclude(&self) -> Vec<String> {
+        self.exclude.clone().unwrap_or_default()
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use config::{Config, File};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::global_user::get_global_config_dir;
+use crate::settings::project::Project;
+
+// The `[tools]` section of wrangler.toml or of the global config, pinning
+// the versions of the tools wrangler installs. Unpinned tools are used from
+// your PATH, or installed at their latest version.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Tools {
+    #[serde(
+        rename = "wasm-pack",
+        alias = "wasm_pack",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub wasm_pack: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wranglerjs: Option<String>,
+}
+
+// Only the `[tools]` of a config file.
+#[derive(Debug, Default, Deserialize)]
+struct ToolsSection {
+    tools: Option<Tools>,
+}
+
+impl Tools {
+    // The versions pinned in the global config.
+    pub fn global() -> Result<Tools, failure::Error> {
+        let config_path = get_global_config_dir()?.join("default.toml");
+        if !config_path.exists() {
+            return Ok(Tools::default());
+        }
+
+        info!("Reading tool versions from {}", config_path.display());
+        read_tools(&config_path)
+    }
+
+    // The versions pinned for a project: those of its wrangler.toml first,
+    // then those of the global config.
+    pub fn for_project(project: &Project) -> Result<Tools, failure::Error> {
+        let tools = project.tools.clone().unwrap_or_default();
+        Ok(tools.or(Tools::global()?))
+    }
+
+    pub fn version(&self, tool_name: &str) -> Option<String> {
+        match tool_name {
+            "wasm-pack" => self.wasm_pack.clone(),
+            "wranglerjs" => self.wranglerjs.clone(),
+            _ => None,
+        }
+        // `v0.8.1` and `0.8.1` are the same release
+        .map(|version| version.trim_start_matches('v').to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Tools::default()
+    }
+
+    fn or(self, other: Tools) -> Tools {
+        Tools {
+            wasm_pack: self.wasm_pack.or(other.wasm_pack),
+            wranglerjs: self.wranglerjs.or(other.wranglerjs),
+        }
+    }
+}
+
+fn read_tools(config_path: &Path) -> Result<Tools, failure::Error> {
+    let mut s = Config::new();
+    s.merge(File::from(config_path))?;
+
+    let section: ToolsSection = s.try_into()?;
+    Ok(section.tools.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn it_reads_pinned_versions_by_tool_name() {
+        let config_path = env::temp_dir().join("it_reads_pinned_versions_by_tool_name.toml");
+        fs::write(
+            &config_path,
+            "email = \"\"\napi_key = \"\"\n\n[tools]\nwasm-pack = \"v0.8.1\"\nwranglerjs = \"1.4.0\"\n",
+        )
+        .unwrap();
+
+        let tools = read_tools(&config_path).unwrap();
+        fs::remove_file(&config_path).unwrap();
+
+        assert_eq!(tools.version("wasm-pack"), Some("0.8.1".to_string()));
+        assert_eq!(tools.version("wranglerjs"), Some("1.4.0".to_string()));
+        assert_eq!(tools.version("wasm-bindgen"), None);
+    }
+
+    #[test]
+    fn it_prefers_the_project_pins_over_the_global_ones() {
+        let project = Tools {
+            wasm_pack: Some("0.8.1".to_string()),
+            ..Tools::default()
+        };
+        let global = Tools {
+            wasm_pack: Some("0.7.0".to_string()),
+            wranglerjs: Some("1.4.0".to_string()),
+        };
+
+        let tools = project.or(global);
+
+        assert_eq!(tools.version("wasm-pack"), Some("0.8.1".to_string()));
+        assert_eq!(tools.version("wranglerjs"), Some("1.4.0".to_string()));
+    }
+}
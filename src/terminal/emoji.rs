@@ -13,16 +13,20 @@
 
 use console::Emoji;
 
+pub static CLIPBOARD: Emoji = Emoji("📋 ", "");
 pub static CRAB: Emoji = Emoji("🦀 ", "");
 pub static DANCERS: Emoji = Emoji("👯 ", "");
+pub static DOWN: Emoji = Emoji("⬇️ ", "");
 pub static EYES: Emoji = Emoji("👀 ", "");
 pub static INBOX: Emoji = Emoji("📥 ", "");
 pub static INFO: Emoji = Emoji("💁‍ ", "");
+pub static MAP: Emoji = Emoji("🗺️ ", "");
 pub static MICROSCOPE: Emoji = Emoji("🔬 ", "");
 pub static SHEEP: Emoji = Emoji("🐑 ", "");
 pub static SLEUTH: Emoji = Emoji("🕵️‍♂️ ", "");
 pub static SPARKLES: Emoji = Emoji("✨ ", "");
 pub static SWIRL: Emoji = Emoji("🌀 ", "");
+pub static TEST_TUBE: Emoji = Emoji("🧪 ", "");
 pub static UP: Emoji = Emoji("🆙 ", "");
 pub static WARN: Emoji = Emoji("⚠️ ", "");
 pub static WAVING: Emoji = Emoji("👋 ", "");
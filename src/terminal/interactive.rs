@@ -0,0 +1,65 @@
+use std::io::{self, BufRead, Write};
+
+// Whether someone is at a terminal to answer questions.
+pub fn is_interactive() -> bool {
+    atty::is(atty::Stream::Stdin)
+}
+
+// Ask a question, returning the answer without surrounding whitespace.
+pub fn ask(question: &str) -> Result<String, failure::Error> {
+    print!("{} ", question);
+    io::stdout().flush()?;
+
+    read_answer(&mut io::stdin().lock())
+}
+
+// The next line of `input`. Running out of input is an error, as no answer
+// is ever coming.
+fn read_answer(input: &mut dyn BufRead) -> Result<String, failure::Error> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        failure::bail!("No answer, the input has ended");
+    }
+    Ok(line.trim().to_string())
+}
+
+// Ask a yes or no question, `default` being the answer to a bare enter.
+pub fn confirm(question: &str, default: bool) -> Result<bool, failure::Error> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    let answer = ask(&format!("{} {}", question, hint))?;
+
+    Ok(match answer.chars().next() {
+        None => default,
+        Some(c) => c == 'y' || c == 'Y',
+    })
+}
+
+// Ask to pick one of `choices`, returning its index.
+pub fn select(question: &str, choices: &[String]) -> Result<usize, failure::Error> {
+    println!("{}", question);
+    for (i, choice) in choices.iter().enumerate() {
+        println!("  {}) {}", i + 1, choice);
+    }
+
+    loop {
+        let answer = ask(&format!("[1-{}]:", choices.len()))?;
+        match answer.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= choices.len() => return Ok(n - 1),
+            _ => println!("Pick a number between 1 and {}", choices.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_answers_until_the_input_ends() {
+        let mut input = io::Cursor::new("  yes \n\n");
+
+        assert_eq!(read_answer(&mut input).unwrap(), "yes");
+        assert_eq!(read_answer(&mut input).unwrap(), "");
+        assert!(read_answer(&mut input).is_err());
+    }
+}
@@ -1,2 +1,3 @@
 pub mod emoji;
+pub mod interactive;
 pub mod message;
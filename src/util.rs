@@ -1,3 +1,10 @@
+use number_prefix::{NumberPrefix, Prefixed, Standalone};
+#[cfg(test)]
+use std::env;
+#[cfg(test)]
+use std::fs;
+#[cfg(test)]
+use std::path::PathBuf;
 use std::process::{Child, Command};
 
 /// wrapper around spawning child processes such that they
@@ -19,3 +26,27 @@ impl Drop for GuardedCommand {
         }
     }
 }
+
+/// a size in bytes the way wrangler prints it, e.g. `1.5 KiB`.
+pub fn human_size(bytes: u64) -> String {
+    match NumberPrefix::binary(bytes as f64) {
+        Standalone(bytes) => format!("{} B", bytes),
+        Prefixed(prefix, n) => format!("{:.1} {}B", n, prefix),
+    }
+}
+
+/// a fresh directory for a test, named `name` in the temp dir and holding
+/// `files`, as paths relative to it and their contents.
+#[cfg(test)]
+pub fn temp_dir_with(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(name);
+    drop(fs::remove_dir_all(&dir));
+    fs::create_dir_all(&dir).unwrap();
+
+    for (file, contents) in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
@@ -38,6 +38,7 @@ fn it_builds_with_webpack_single_js() {
 
     build(fixture);
     assert!(fixture_out_path(fixture).join("script.js").exists());
+    assert!(fixture_out_path(fixture).join("script.js.map").exists());
     cleanup(fixture);
 }
 
@@ -95,7 +96,7 @@ fn it_fails_with_multiple_webpack_configs() {
         type = "Webpack"
     "#};
 
-    build_fails_with(fixture, "Multiple webpack configurations are not supported. You can specify a different path for your webpack configuration file in wrangler.toml with the `webpack_config` field");
+    build_fails_with(fixture, "Your webpack configuration exports multiple configurations. Pick one with the `webpack_config_select` field in wrangler.toml");
     cleanup(fixture);
 }
 
@@ -109,7 +110,40 @@ fn it_fails_with_multiple_specify_webpack_configs() {
         webpack_config = "webpack.worker.js"
     "#};
 
-    build_fails_with(fixture, "Multiple webpack configurations are not supported. You can specify a different path for your webpack configuration file in wrangler.toml with the `webpack_config` field");
+    build_fails_with(fixture, "Your webpack configuration exports multiple configurations. Pick one with the `webpack_config_select` field in wrangler.toml");
+    cleanup(fixture);
+}
+
+#[test]
+fn it_builds_with_multiple_named_webpack_configs() {
+    // the fixture is shared, so the selections are built one after the other
+    let fixture = "webpack_multiple_named_config";
+
+    for (select, expected) in &[("site", "Hello from site!"), ("0", "Hello from api!")] {
+        create_temporary_copy(fixture);
+
+        settings! {fixture, format!(r#"
+            type = "Webpack"
+            webpack_config_select = "{}"
+        "#, select)};
+
+        build(fixture);
+        let script = fs::read_to_string(fixture_out_path(fixture).join("script.js")).unwrap();
+        assert!(script.contains(expected));
+        cleanup(fixture);
+    }
+
+    create_temporary_copy(fixture);
+
+    settings! {fixture, r#"
+        type = "Webpack"
+        webpack_config_select = "all"
+    "#};
+
+    build(fixture);
+    assert!(fixture_out_path(fixture).join("bundles.json").exists());
+    assert!(fixture_out_path(fixture).join("api/script.js").exists());
+    assert!(fixture_out_path(fixture).join("site/script.js").exists());
     cleanup(fixture);
 }
 
@@ -129,6 +163,23 @@ fn it_builds_with_webpack_wast() {
     cleanup(fixture);
 }
 
+#[test]
+fn it_builds_with_multiple_webpack_wasm_modules() {
+    let fixture = "webpack_multiple_wast";
+    create_temporary_copy(fixture);
+
+    settings! {fixture, r#"
+        type = "Webpack"
+    "#};
+
+    build(fixture);
+    assert!(fixture_out_path(fixture).join("script.js").exists());
+    assert!(fixture_out_path(fixture).join("add.wasm").exists());
+    assert!(fixture_out_path(fixture).join("sub.wasm").exists());
+
+    cleanup(fixture);
+}
+
 fn cleanup(fixture: &str) {
     let path = fixture_path(fixture);
     assert!(path.exists(), format!("{:?} does not exist", path));
@@ -0,0 +1,4 @@
+module.exports = [
+  { name: "api", entry: "./api.js" },
+  { name: "site", entry: "./site.js" }
+];
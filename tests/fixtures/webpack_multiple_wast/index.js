@@ -0,0 +1,4 @@
+(async function() {
+    await import("./add.wast");
+    await import("./sub.wast");
+})()
@@ -2,7 +2,7 @@ use assert_cmd::prelude::*;
 
 use std::fs;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 #[test]
 fn it_generates_with_defaults() {
@@ -32,6 +32,74 @@ fn it_generates_with_arguments() {
     cleanup(name);
 }
 
+#[test]
+fn it_generates_from_a_local_template() {
+    let name = "local-template-worker";
+    let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    wrangler
+        .arg("generate")
+        .arg(name)
+        .arg("tests/fixtures/template")
+        .args([
+            "--define",
+            "greeting=hello",
+            "--define",
+            "account_id=0123abcd",
+        ])
+        .arg("--no-setup")
+        .assert()
+        .success();
+
+    let package = fs::read_to_string(format!("{}/package.json", name)).unwrap();
+    assert!(package.contains("\"name\": \"local-template-worker\""));
+    let index = fs::read_to_string(format!("{}/src/index.js", name)).unwrap();
+    assert!(index.contains("hello from local-template-worker"));
+    let wranglertoml = fs::read_to_string(format!("{}/wrangler.toml", name)).unwrap();
+    assert!(wranglertoml.contains("account_id = \"0123abcd\""));
+
+    assert_eq!(Path::new(&format!("{}/TEMPLATE.md", name)).exists(), false);
+    assert_eq!(Path::new(&format!("{}/.genignore", name)).exists(), false);
+    assert_eq!(
+        Path::new(&format!("{}/wrangler-template.toml", name)).exists(),
+        false
+    );
+    assert_eq!(Path::new(&format!("{}/SETUP", name)).exists(), false);
+    cleanup(name);
+}
+
+#[test]
+fn it_runs_the_setup_of_a_template() {
+    let name = "set-up-template-worker";
+    let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    wrangler
+        .arg("generate")
+        .arg(name)
+        .arg("tests/fixtures/template")
+        .args(["--define", "greeting=hello", "--yes"])
+        .assert()
+        .success();
+
+    assert_eq!(Path::new(&format!("{}/SETUP", name)).exists(), true);
+    cleanup(name);
+}
+
+#[test]
+fn it_skips_the_setup_commands_of_a_template_unless_confirmed() {
+    let name = "unconfirmed-template-worker";
+    let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    wrangler
+        .arg("generate")
+        .arg(name)
+        .arg("tests/fixtures/template")
+        .args(["--define", "greeting=hello"])
+        .stdin(Stdio::null())
+        .assert()
+        .success();
+
+    assert_eq!(Path::new(&format!("{}/SETUP", name)).exists(), false);
+    cleanup(name);
+}
+
 pub fn generate(name: Option<&str>, template: Option<&str>, project_type: Option<&str>) {
     let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
     if name.is_none() && template.is_none() && project_type.is_none() {
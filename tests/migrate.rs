@@ -0,0 +1,47 @@
+use assert_cmd::prelude::*;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[test]
+fn it_migrates_kv_namespace_names() {
+    let name = "migrate1";
+    let wranglertoml_path = Path::new(name).join("wrangler.toml");
+    fs::create_dir_all(name).unwrap();
+    fs::write(
+        &wranglertoml_path,
+        "name = \"worker\"\ntype = \"webpack\"\naccount_id = \"\"\n# bound namespaces\nkv-namespaces = [\"CACHE\"]\n",
+    )
+    .unwrap();
+
+    // without a terminal to confirm on, nothing changes without --yes
+    migrate(name).assert().failure();
+    assert!(fs::read_to_string(&wranglertoml_path)
+        .unwrap()
+        .contains("kv-namespaces = [\"CACHE\"]"));
+
+    migrate(name).arg("--yes").assert().success();
+    assert_eq!(
+        fs::read_to_string(&wranglertoml_path).unwrap(),
+        "name = \"worker\"\ntype = \"webpack\"\naccount_id = \"\"\n\n# bound namespaces\n[[kv-namespaces]]\nbinding = \"CACHE\"\nid = \"\"\n"
+    );
+
+    cleanup(name);
+}
+
+fn migrate(name: &str) -> Command {
+    let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    wrangler
+        .arg("migrate")
+        .current_dir(name)
+        .stdin(Stdio::null())
+        // no credentials, so no namespace ids are looked up
+        .env("WRANGLER_HOME", Path::new(name).join("home"));
+    wrangler
+}
+
+fn cleanup(name: &str) {
+    fs::remove_dir_all(name).unwrap();
+    assert!(!Path::new(name).exists());
+}
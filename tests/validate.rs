@@ -0,0 +1,61 @@
+use assert_cmd::prelude::*;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn it_warns_about_unknown_keys() {
+    let name = "validate1";
+    project(
+        name,
+        "name = \"worker\"\ntype = \"javascript\"\naccount_id = \"\"\nzoneid = \"\"\n",
+    );
+
+    let output = validate(name).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("wrangler.toml:4:1: unknown key `zoneid`, did you mean `zone_id`?"));
+
+    cleanup(name);
+}
+
+#[test]
+fn it_fails_on_type_errors_and_duplicate_bindings() {
+    let name = "validate2";
+    project(
+        name,
+        "name = \"worker\"\ntype = \"javascript\"\naccount_id = \"\"\nprivate = \"maybe\"\n",
+    );
+    validate(name).assert().failure();
+
+    project(
+        name,
+        "name = \"worker\"\ntype = \"javascript\"\naccount_id = \"\"\n\n[[kv-namespaces]]\nbinding = \"KV\"\nid = \"a\"\n\n[[kv-namespaces]]\nbinding = \"KV\"\nid = \"b\"\n",
+    );
+    validate(name).assert().failure();
+
+    cleanup(name);
+}
+
+fn project(name: &str, wrangler_toml: &str) {
+    fs::create_dir_all(name).unwrap();
+    fs::write(
+        Path::new(name).join("package.json"),
+        "{\"main\": \"index.js\"}",
+    )
+    .unwrap();
+    fs::write(Path::new(name).join("index.js"), "").unwrap();
+    fs::write(Path::new(name).join("wrangler.toml"), wrangler_toml).unwrap();
+}
+
+fn validate(name: &str) -> Command {
+    let mut wrangler = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    wrangler.arg("validate").current_dir(name);
+    wrangler
+}
+
+fn cleanup(name: &str) {
+    fs::remove_dir_all(name).unwrap();
+    assert!(!Path::new(name).exists());
+}
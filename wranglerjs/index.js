@@ -1,6 +1,7 @@
 const webpack = require("webpack");
-const { join } = require("path");
+const { basename, join } = require("path");
 const fs = require("fs");
+const zlib = require("zlib");
 const WasmMainTemplatePlugin = require("webpack/lib/wasm/WasmMainTemplatePlugin");
 
 function error(msg) {
@@ -15,7 +16,8 @@ const args = rawArgs.reduce((obj, e) => {
     throw error("malformed arguments");
   }
 
-  const [name, value] = e.split("=");
+  const [name, ...rest] = e.split("=");
+  const value = rest.join("=");
   const normalizedName = name.replace("--", "");
   obj[normalizedName] = value;
   return obj;
@@ -28,69 +30,260 @@ if (args["no-webpack-config"] === "1") {
   config = require(join(process.cwd(), args["webpack-config"]));
 }
 
-if (Array.isArray(config)) {
+// Pick one configuration out of an exported array, by `name` or by index.
+function selectConfig(configs, select) {
+  const byName = configs.find(c => c.name === select);
+  if (byName !== undefined) {
+    return byName;
+  }
+
+  const index = Number(select);
+  if (Number.isInteger(index) && configs[index] !== undefined) {
+    return configs[index];
+  }
+
   throw error(
-    "Multiple webpack configurations are not supported. You can specify a different path for your webpack configuration file in wrangler.toml with the `webpack_config` field\n" +
-      "Please make sure that your webpack configuration exports an Object."
+    `No webpack configuration named or at index \`${select}\`. Check the \`webpack_config_select\` field in wrangler.toml.`
   );
 }
 
+const select = args["config-select"];
+if (Array.isArray(config)) {
+  if (select === undefined) {
+    throw error(
+      "Your webpack configuration exports multiple configurations. Pick one with the `webpack_config_select` field in wrangler.toml, by its `name` or index, or set it to \"all\" to build each configuration as its own worker. You can also specify a different path for your webpack configuration file with the `webpack_config` field"
+    );
+  } else if (select !== "all") {
+    config = selectConfig(config, select);
+  }
+}
+
+// Source maps let wrangler map stack traces back to the original sources.
+[].concat(config).forEach(c => {
+  if (c.devtool === undefined) {
+    c.devtool = "source-map";
+  }
+});
+
 const compiler = webpack(config);
-const fullConfig = compiler.options;
+const isMultiCompiler = compiler.compilers !== undefined;
+const compilers = isMultiCompiler ? compiler.compilers : [compiler];
 
 function filterByExtension(ext) {
   return v => v.indexOf("." + ext) !== -1;
 }
 
+// Every wasm module gets a name derived from its source file, stable across
+// builds. wrangler writes the module under that name and uploads it with the
+// binding the runtime below resolves it with.
+function wasmModuleNames(compilation) {
+  const modules = compilation.modules
+    .filter(m => m.type && m.type.startsWith("webassembly"))
+    .sort((a, b) => (a.resource < b.resource ? -1 : a.resource > b.resource ? 1 : 0));
+
+  const names = new Map();
+  const used = new Set();
+  modules.forEach(m => {
+    const base = basename(m.resource || String(m.id))
+      .replace(/\?.*$/, "")
+      .replace(/\.[^.]*$/, "")
+      .replace(/[^A-Za-z0-9_]/g, "_");
+
+    let name = base;
+    for (let i = 1; used.has(name); i++) {
+      name = `${base}_${i}`;
+    }
+    used.add(name);
+    names.set(m, name);
+  });
+  return names;
+}
+
+function wasmBinding(name) {
+  return `${args["wasm-binding"]}_${name}`;
+}
+
+// The wasm module each emitted wasm asset comes from, per compilation.
+const wasmAssetModules = new WeakMap();
+
 // Override the {FetchCompileWasmTemplatePlugin} and inject our new runtime.
-const [
-  fetchCompileWasmTemplatePlugin
-] = compiler.hooks.thisCompilation.taps.filter(
-  tap => tap.name === "FetchCompileWasmTemplatePlugin"
-);
-fetchCompileWasmTemplatePlugin.fn = function(compilation) {
-  const mainTemplate = compilation.mainTemplate;
-  const generateLoadBinaryCode = () => `
-      // Fake fetch response
-      Promise.resolve({
-        arrayBuffer() { return Promise.resolve(${args["wasm-binding"]}); }
+compilers.forEach(compiler => {
+  const [
+    fetchCompileWasmTemplatePlugin
+  ] = compiler.hooks.thisCompilation.taps.filter(
+    tap => tap.name === "FetchCompileWasmTemplatePlugin"
+  );
+  fetchCompileWasmTemplatePlugin.fn = function(compilation) {
+    const mainTemplate = compilation.mainTemplate;
+    // {wasmModuleId} is in scope where webpack inlines this code
+    const generateLoadBinaryCode = () => {
+      const bindings = [];
+      wasmModuleNames(compilation).forEach((name, m) => {
+        bindings.push(`${JSON.stringify(m.id)}: ${wasmBinding(name)}`);
       });
-    `;
 
-  const plugin = new WasmMainTemplatePlugin({
-    generateLoadBinaryCode,
-    mangleImports: false,
-    supportsStreaming: false
+      return `
+        // Fake fetch response, with the binding of the requested module
+        Promise.resolve({
+          arrayBuffer() { return Promise.resolve({ ${bindings.join(", ")} }[wasmModuleId]); }
+        });
+      `;
+    };
+
+    const plugin = new WasmMainTemplatePlugin({
+      generateLoadBinaryCode,
+      mangleImports: false,
+      supportsStreaming: false
+    });
+    plugin.apply(mainTemplate);
+  };
+
+  // Tapped after {WebAssemblyModulesPlugin}, so its wasm entries are in the
+  // manifest already.
+  compiler.hooks.compilation.tap("wranglerjs", compilation => {
+    const assetModules = new Map();
+    wasmAssetModules.set(compilation, assetModules);
+
+    compilation.chunkTemplate.hooks.renderManifest.tap(
+      "wranglerjs",
+      result => {
+        result
+          .filter(entry => entry.identifier.startsWith("webassemblyModule"))
+          .forEach(entry => {
+            const file = compilation.getPath(
+              entry.filenameTemplate,
+              entry.pathOptions
+            );
+            assetModules.set(file, entry.pathOptions.module);
+          });
+        return result;
+      }
+    );
   });
-  plugin.apply(mainTemplate);
-};
+});
+
+// Collect the script and wasm modules a single compilation produced.
+function bundleAssets(stats) {
+  const compilation = stats.compilation;
+  const assets = compilation.assets;
+  const wasmAssets = Object.keys(assets).filter(filterByExtension("wasm"));
+  const jsAssets = Object.keys(assets)
+    .filter(filterByExtension("js"))
+    .filter(k => !filterByExtension("map")(k));
+
+  // The assets are concatenated, so their source maps become the sections of
+  // an index map, each starting where its asset does in the script.
+  const sections = [];
+  const script = jsAssets.reduce((acc, k) => {
+    const asset = assets[k];
+    const map = assets[k + ".map"];
+    if (map !== undefined) {
+      const lines = acc.split("\n");
+      sections.push({
+        offset: {
+          line: lines.length - 1,
+          column: lines[lines.length - 1].length
+        },
+        map: JSON.parse(map.source())
+      });
+    }
+    return acc + asset.source();
+  }, "");
+
+  let source_map = null;
+  if (sections.length > 0) {
+    source_map = JSON.stringify({ version: 3, sections });
+  }
+
+  const names = wasmModuleNames(compilation);
+  const assetModules = wasmAssetModules.get(compilation) || new Map();
+  const wasm_modules = wasmAssets.map(k => {
+    const m = assetModules.get(k);
+    if (m === undefined || !names.has(m)) {
+      throw error(`Could not find the wasm module emitted as ${k}`);
+    }
+
+    return {
+      name: names.get(m),
+      wasm: Buffer.from(assets[k].source()).toString("base64")
+    };
+  });
+
+  return { script, wasm_modules, source_map };
+}
+
+// The size of every module webpack bundled, for `wrangler build --analyze`.
+// WebAssembly modules are reported by wrangler from the emitted assets.
+function moduleStats(compilation) {
+  const stats = [];
+  const collect = m => {
+    // modules concatenated into one by scope hoisting
+    if (m.modules !== undefined) {
+      m.modules.forEach(collect);
+      return;
+    }
+    if (m.type && m.type.startsWith("webassembly")) {
+      return;
+    }
+
+    const source =
+      typeof m.originalSource === "function" ? m.originalSource() : null;
+    if (!source) {
+      return;
+    }
+
+    const buffer = Buffer.from(source.source());
+    stats.push({
+      name: m.readableIdentifier(compilation.requestShortener),
+      size: buffer.length,
+      gzip_size: zlib.gzipSync(buffer).length
+    });
+  };
+
+  compilation.modules.forEach(collect);
+  return stats;
+}
 
 let lastHash = "";
 const compilerCallback = (err, stats) => {
   if (err) {
+    // In watch mode, report the error to wrangler and wait for the next change
+    if (args["watch"] === "1") {
+      const bundle = { wasm_modules: [], script: "", errors: [err.toString()] };
+      fs.writeFileSync(args["output-file"], JSON.stringify(bundle));
+      return;
+    }
     throw err;
   }
 
   if (stats.hash !== lastHash) {
-    const assets = stats.compilation.assets;
     const jsonStats = stats.toJson();
-    const bundle = {
-      wasm: null,
+    const childStats = isMultiCompiler ? stats.stats : [stats];
+    const startTime = Math.min(...childStats.map(s => s.startTime));
+    const endTime = Math.max(...childStats.map(s => s.endTime));
+
+    let bundle = {
+      wasm_modules: [],
       script: "",
-      errors: jsonStats.errors
+      errors: jsonStats.errors,
+      warnings: jsonStats.warnings,
+      time: endTime - startTime
     };
 
-    const wasmModuleAsset = Object.keys(assets).find(filterByExtension("wasm"));
-    const jsAssets = Object.keys(assets).filter(filterByExtension("js"));
-    const hasWasmModule = wasmModuleAsset !== undefined;
-
-    bundle.script = jsAssets.reduce((acc, k) => {
-      const asset = assets[k];
-      return acc + asset.source();
-    }, "");
+    if (isMultiCompiler) {
+      // each configuration becomes its own worker, named after the
+      // configuration or its index
+      bundle.bundles = childStats.map((s, i) =>
+        Object.assign({ name: s.compilation.name || String(i) }, bundleAssets(s))
+      );
+    } else {
+      bundle = Object.assign(bundle, bundleAssets(stats));
+    }
 
-    if (hasWasmModule === true) {
-      bundle.wasm = Buffer.from(assets[wasmModuleAsset].source()).toString("base64");
+    if (args["analyze"] === "1") {
+      bundle.modules = [].concat(
+        ...childStats.map(s => moduleStats(s.compilation))
+      );
     }
 
     fs.writeFileSync(args["output-file"], JSON.stringify(bundle));
@@ -99,7 +292,15 @@ const compilerCallback = (err, stats) => {
 };
 
 if (args["watch"] === "1") {
-  compiler.watch(fullConfig.watchOptions, compilerCallback);
+  // Globs from wrangler are relative to the project root, webpack matches
+  // them against absolute paths.
+  const ignored = JSON.parse(args["watch-ignore"] || "[]").map(glob =>
+    join(process.cwd(), glob)
+  );
+  const watchOptions = Object.assign({}, compilers[0].options.watchOptions);
+  watchOptions.ignored = [].concat(watchOptions.ignored || [], ignored);
+
+  compiler.watch(watchOptions, compilerCallback);
 } else {
   compiler.run(compilerCallback);
 }